@@ -4,6 +4,8 @@ mod evented;
 mod events;
 mod poll;
 mod registration;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod sys;
 
 pub mod event {
     pub use crate::evented::Evented;
@@ -12,6 +14,8 @@ pub mod event {
 }
 pub mod net;
 #[cfg(all(unix, not(target_os = "fuchsia")))]
+pub mod process;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
 pub mod unix;
 
 pub use event::Events;
@@ -0,0 +1,231 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::net::UdpSocket;
+use crate::{Evented, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+
+// Large enough for any UDP datagram.
+const MAX_DATAGRAM: usize = 65_536;
+
+/// Splits the traffic of a single bound `UdpSocket` into one `UdpSession`
+/// per remote peer.
+///
+/// The demultiplexer is registered for readable readiness like the socket
+/// itself. On each readable event, `accept` must be called until it returns
+/// `WouldBlock`: it hands out sessions for new peers and queues the
+/// datagrams of known peers on their sessions, which become readable.
+pub struct UdpDemux {
+    shared: Arc<Shared>,
+    capacity: usize,
+    buf: Mutex<Vec<u8>>,
+    dropped: AtomicUsize,
+}
+
+struct Shared {
+    socket: UdpSocket,
+    sessions: Mutex<HashMap<SocketAddr, Arc<Queue>>>,
+}
+
+struct Queue {
+    datagrams: Mutex<VecDeque<Vec<u8>>>,
+    set_readiness: SetReadiness,
+}
+
+impl UdpDemux {
+    /// Creates a demultiplexer over `socket`, queueing at most `capacity`
+    /// datagrams per session. Further datagrams for a full session are
+    /// dropped and counted by `dropped`.
+    pub fn new(socket: UdpSocket, capacity: usize) -> UdpDemux {
+        UdpDemux {
+            shared: Arc::new(Shared {
+                socket,
+                sessions: Mutex::new(HashMap::new()),
+            }),
+            capacity,
+            buf: Mutex::new(vec![0; MAX_DATAGRAM]),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of datagrams dropped so far because their session
+    /// was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the underlying socket.
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.shared.socket
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.shared.socket.local_addr()
+    }
+
+    /// Reads datagrams from the socket until one arrives from a peer without
+    /// a session, and returns a new session for that peer with the datagram
+    /// queued on it.
+    pub fn accept(&self) -> io::Result<(UdpSession, SocketAddr)> {
+        let mut buf = self.buf.lock().unwrap();
+        loop {
+            let (n, peer) = self.shared.socket.recv_from(&mut buf)?;
+            let datagram = buf[..n].to_vec();
+
+            let mut sessions = self.shared.sessions.lock().unwrap();
+            if let Some(queue) = sessions.get(&peer) {
+                queue.push(datagram, self.capacity, &self.dropped);
+                continue;
+            }
+
+            let (registration, set_readiness) = Registration::new2();
+            let queue = Arc::new(Queue {
+                datagrams: Mutex::new(VecDeque::new()),
+                set_readiness,
+            });
+            queue.push(datagram, self.capacity, &self.dropped);
+            sessions.insert(peer, queue.clone());
+
+            let session = UdpSession {
+                shared: self.shared.clone(),
+                peer,
+                queue,
+                registration,
+            };
+            return Ok((session, peer));
+        }
+    }
+}
+
+impl Queue {
+    // A session that cannot be made readable still gets the datagram, and
+    // does not stop the other sessions from being fed.
+    fn push(&self, datagram: Vec<u8>, capacity: usize, dropped: &AtomicUsize) {
+        let mut datagrams = self.datagrams.lock().unwrap();
+        if datagrams.len() < capacity {
+            datagrams.push_back(datagram);
+        } else {
+            dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        drop(self.set_readiness.set_readiness(Ready::readable()));
+    }
+}
+
+impl Evented for UdpDemux {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.shared.socket.register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.shared.socket.reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.shared.socket.deregister(poll)
+    }
+}
+
+impl fmt::Debug for UdpDemux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpDemux")
+            .field("socket", &self.shared.socket)
+            .field("capacity", &self.capacity)
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+/// The traffic of a single peer of a `UdpDemux`.
+///
+/// A session can only be registered for readable readiness. Dropping it
+/// forgets the peer, so its next datagram is returned by `accept` again.
+pub struct UdpSession {
+    shared: Arc<Shared>,
+    peer: SocketAddr,
+    queue: Arc<Queue>,
+    registration: Registration,
+}
+
+impl UdpSession {
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Takes the next queued datagram from the peer, and returns the number
+    /// of bytes copied into `buf` and whether the datagram was truncated to
+    /// fit.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<(usize, bool)> {
+        let mut datagrams = self.queue.datagrams.lock().unwrap();
+        let datagram = match datagrams.pop_front() {
+            Some(datagram) => datagram,
+            None => return Err(io::ErrorKind::WouldBlock.into()),
+        };
+        if datagrams.is_empty() {
+            self.queue.set_readiness.set_readiness(Ready::empty())?;
+        }
+        let n = datagram.len().min(buf.len());
+        buf[..n].copy_from_slice(&datagram[..n]);
+        Ok((n, n < datagram.len()))
+    }
+
+    /// Sends a datagram to the peer from the shared socket.
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.shared.socket.send_to(buf, &self.peer)
+    }
+}
+
+impl Evented for UdpSession {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.registration.deregister(poll)
+    }
+}
+
+impl Drop for UdpSession {
+    fn drop(&mut self) {
+        // A peer only gets a new session once the old one is removed here.
+        self.shared.sessions.lock().unwrap().remove(&self.peer);
+    }
+}
+
+impl fmt::Debug for UdpSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpSession")
+            .field("peer", &self.peer)
+            .finish()
+    }
+}
@@ -1,5 +1,19 @@
+mod demux;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod msg;
+#[cfg(target_os = "linux")]
+mod splice;
 mod tcp;
 mod udp;
 
+pub use demux::{UdpDemux, UdpSession};
+#[cfg(target_os = "linux")]
+pub use msg::ExtendedError;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+pub use msg::{Ecn, MsgFlags, RecvMeta, SendMeta};
+#[cfg(target_os = "linux")]
+pub use splice::Splice;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+pub use tcp::FdReserve;
 pub use tcp::{TcpListener, TcpStream};
 pub use udp::UdpSocket;
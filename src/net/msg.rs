@@ -0,0 +1,508 @@
+use std::fmt;
+#[cfg(target_os = "linux")]
+use std::io;
+#[cfg(target_os = "linux")]
+use std::mem;
+use std::net::{IpAddr, SocketAddr};
+#[cfg(target_os = "linux")]
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops;
+#[cfg(target_os = "linux")]
+use std::ptr;
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+use std::time::SystemTime;
+#[cfg(target_os = "linux")]
+use std::time::UNIX_EPOCH;
+
+use libc::c_int;
+
+#[cfg(target_os = "linux")]
+use crate::sys;
+use crate::sys::cmsg;
+
+/// A set of flags for the `send_with_flags` and `recv_with_flags` family of
+/// socket methods.
+///
+/// `MSG_DONTWAIT` is always added, since the sockets of this crate never
+/// block. `MSG_WAITALL` is deliberately not offered for the same reason.
+#[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
+pub struct MsgFlags(c_int);
+
+impl MsgFlags {
+    pub fn empty() -> MsgFlags {
+        MsgFlags(0)
+    }
+
+    /// `MSG_MORE`: more data is coming, so the kernel may hold back this
+    /// data to batch it with the next send.
+    #[cfg(target_os = "linux")]
+    pub fn more() -> MsgFlags {
+        MsgFlags(libc::MSG_MORE)
+    }
+
+    /// `MSG_NOSIGNAL`: report `EPIPE` instead of raising `SIGPIPE` when the
+    /// peer has closed the connection.
+    #[cfg(target_os = "linux")]
+    pub fn nosignal() -> MsgFlags {
+        MsgFlags(libc::MSG_NOSIGNAL)
+    }
+
+    /// `MSG_PEEK`: return received data without removing it from the queue.
+    pub fn peek() -> MsgFlags {
+        MsgFlags(libc::MSG_PEEK)
+    }
+
+    /// `MSG_TRUNC`: on a datagram socket, return the real length of the
+    /// datagram even if it was longer than the buffer. On a stream socket,
+    /// discard the received data instead of copying it.
+    pub fn trunc() -> MsgFlags {
+        MsgFlags(libc::MSG_TRUNC)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn is_more(&self) -> bool {
+        self.contains(MsgFlags::more())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn is_nosignal(&self) -> bool {
+        self.contains(MsgFlags::nosignal())
+    }
+
+    pub fn is_peek(&self) -> bool {
+        self.contains(MsgFlags::peek())
+    }
+
+    pub fn is_trunc(&self) -> bool {
+        self.contains(MsgFlags::trunc())
+    }
+
+    pub fn insert<T: Into<Self>>(&mut self, other: T) {
+        self.0 |= other.into().0;
+    }
+
+    pub fn remove<T: Into<Self>>(&mut self, other: T) {
+        self.0 &= !other.into().0;
+    }
+
+    pub fn contains<T: Into<Self>>(&self, other: T) -> bool {
+        let other = other.into();
+        (*self & other) == other
+    }
+
+    pub(crate) fn bits(self) -> c_int {
+        self.0 | libc::MSG_DONTWAIT
+    }
+}
+
+impl<T: Into<MsgFlags>> ops::BitOr<T> for MsgFlags {
+    type Output = MsgFlags;
+
+    fn bitor(self, other: T) -> MsgFlags {
+        MsgFlags(self.0 | other.into().0)
+    }
+}
+
+impl<T: Into<MsgFlags>> ops::BitAnd<T> for MsgFlags {
+    type Output = MsgFlags;
+
+    fn bitand(self, other: T) -> MsgFlags {
+        MsgFlags(self.0 & other.into().0)
+    }
+}
+
+impl<T: Into<MsgFlags>> ops::BitOrAssign<T> for MsgFlags {
+    fn bitor_assign(&mut self, other: T) {
+        self.0 |= other.into().0;
+    }
+}
+
+impl fmt::Debug for MsgFlags {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut one = false;
+        let flags = [
+            #[cfg(target_os = "linux")]
+            (MsgFlags::more(), "More"),
+            #[cfg(target_os = "linux")]
+            (MsgFlags::nosignal(), "NoSignal"),
+            (MsgFlags::peek(), "Peek"),
+            (MsgFlags::trunc(), "Trunc"),
+        ];
+
+        for &(flag, msg) in &flags {
+            if self.contains(flag) {
+                if one {
+                    write!(fmt, " | ")?
+                }
+                write!(fmt, "{}", msg)?;
+
+                one = true
+            }
+        }
+
+        if !one {
+            fmt.write_str("(empty)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An explicit congestion notification codepoint, carried in the low two
+/// bits of the IPv4 type of service or IPv6 traffic class byte.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Ecn {
+    /// Not ECN-capable transport.
+    NotEct,
+    /// ECN-capable transport, ECT(1).
+    Ect1,
+    /// ECN-capable transport, ECT(0).
+    Ect0,
+    /// Congestion experienced.
+    Ce,
+}
+
+impl Ecn {
+    fn from_tos(tos: u8) -> Ecn {
+        match tos & 0b11 {
+            0b00 => Ecn::NotEct,
+            0b01 => Ecn::Ect1,
+            0b10 => Ecn::Ect0,
+            _ => Ecn::Ce,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn to_tos(self) -> u8 {
+        match self {
+            Ecn::NotEct => 0b00,
+            Ecn::Ect1 => 0b01,
+            Ecn::Ect0 => 0b10,
+            Ecn::Ce => 0b11,
+        }
+    }
+}
+
+/// Information about a datagram received with `UdpSocket::recv_from_bufs`
+/// and related methods.
+#[derive(Debug, Clone)]
+pub struct RecvMeta {
+    len: usize,
+    addr: SocketAddr,
+    truncated: bool,
+    segment_size: Option<usize>,
+    dst_addr: Option<IpAddr>,
+    interface: Option<u32>,
+    timestamp: Option<SystemTime>,
+    tos: Option<u8>,
+    ttl: Option<u8>,
+}
+
+impl RecvMeta {
+    pub(crate) fn new(len: usize, addr: SocketAddr, flags: c_int) -> RecvMeta {
+        RecvMeta {
+            len,
+            addr,
+            truncated: flags & libc::MSG_TRUNC != 0,
+            segment_size: None,
+            dst_addr: None,
+            interface: None,
+            timestamp: None,
+            tos: None,
+            ttl: None,
+        }
+    }
+
+    pub(crate) fn with_control(mut self, control: &[u8]) -> RecvMeta {
+        for cmsg in cmsg::iter(control) {
+            match (cmsg.level, cmsg.ty) {
+                #[cfg(target_os = "linux")]
+                (libc::SOL_UDP, libc::UDP_GRO) => {
+                    self.segment_size = cmsg.read::<c_int>().map(|size| size as usize);
+                }
+                #[cfg(target_os = "linux")]
+                (libc::IPPROTO_IP, libc::IP_PKTINFO) => {
+                    if let Some(info) = cmsg.read::<libc::in_pktinfo>() {
+                        let addr = Ipv4Addr::from(info.ipi_addr.s_addr.to_ne_bytes());
+                        self.dst_addr = Some(IpAddr::V4(addr));
+                        self.interface = Some(info.ipi_ifindex as u32);
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => {
+                    if let Some(info) = cmsg.read::<libc::in6_pktinfo>() {
+                        self.dst_addr = Some(IpAddr::V6(Ipv6Addr::from(info.ipi6_addr.s6_addr)));
+                        self.interface = Some(info.ipi6_ifindex);
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMPNS) => {
+                    if let Some(ts) = cmsg.read::<libc::timespec>() {
+                        self.timestamp =
+                            Some(UNIX_EPOCH + Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32));
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                (libc::IPPROTO_IP, libc::IP_TOS) => self.tos = cmsg.read::<u8>(),
+                #[cfg(target_os = "linux")]
+                (libc::IPPROTO_IPV6, libc::IPV6_TCLASS) => {
+                    self.tos = cmsg.read::<c_int>().map(|tclass| tclass as u8);
+                }
+                #[cfg(target_os = "linux")]
+                (libc::IPPROTO_IP, libc::IP_TTL) | (libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT) => {
+                    self.ttl = cmsg.read::<c_int>().map(|ttl| ttl as u8);
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Returns the number of bytes received.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the address the datagram was sent from.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns whether the datagram was longer than the buffers it was
+    /// received into, in which case the rest of it was discarded.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the size of the datagrams that were coalesced into this one
+    /// by UDP receive offload, see `UdpSocket::set_gro`. Every segment but
+    /// the last has exactly this size.
+    pub fn segment_size(&self) -> Option<usize> {
+        self.segment_size
+    }
+
+    /// Returns the local address the datagram was sent to, if
+    /// `UdpSocket::set_recv_pktinfo` is enabled.
+    pub fn dst_addr(&self) -> Option<IpAddr> {
+        self.dst_addr
+    }
+
+    /// Returns the index of the interface the datagram arrived on, if
+    /// `UdpSocket::set_recv_pktinfo` is enabled.
+    pub fn interface(&self) -> Option<u32> {
+        self.interface
+    }
+
+    /// Returns when the kernel received the datagram, if
+    /// `UdpSocket::set_recv_timestamp` is enabled.
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Returns the IPv4 type of service or IPv6 traffic class byte of the
+    /// datagram, if `UdpSocket::set_recv_tos` is enabled.
+    pub fn tos(&self) -> Option<u8> {
+        self.tos
+    }
+
+    /// Returns the ECN codepoint of the datagram, if `UdpSocket::set_recv_tos`
+    /// is enabled.
+    pub fn ecn(&self) -> Option<Ecn> {
+        self.tos.map(Ecn::from_tos)
+    }
+
+    /// Returns the IPv4 time to live or IPv6 hop limit the datagram arrived
+    /// with, if `UdpSocket::set_recv_ttl` is enabled.
+    pub fn ttl(&self) -> Option<u8> {
+        self.ttl
+    }
+}
+
+/// Per-datagram options for `UdpSocket::send_msg`.
+#[derive(Debug, Clone, Default)]
+pub struct SendMeta {
+    src_addr: Option<IpAddr>,
+    interface: Option<u32>,
+    ecn: Option<Ecn>,
+}
+
+impl SendMeta {
+    pub fn new() -> SendMeta {
+        SendMeta::default()
+    }
+
+    /// Sends the datagram from `addr`, which must be a local address.
+    #[cfg(target_os = "linux")]
+    pub fn src_addr(mut self, addr: IpAddr) -> SendMeta {
+        self.src_addr = Some(addr);
+        self
+    }
+
+    /// Sends the datagram out of the interface with the given index.
+    #[cfg(target_os = "linux")]
+    pub fn interface(mut self, index: u32) -> SendMeta {
+        self.interface = Some(index);
+        self
+    }
+
+    /// Marks the datagram with the given ECN codepoint (`IP_TOS` or
+    /// `IPV6_TCLASS`), keeping the DSCP bits configured on the socket.
+    #[cfg(target_os = "linux")]
+    pub fn ecn(mut self, ecn: Ecn) -> SendMeta {
+        self.ecn = Some(ecn);
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn has_ecn(&self) -> bool {
+        self.ecn.is_some()
+    }
+
+    // `tos` is the socket's own TOS or traffic class, whose DSCP bits the
+    // ECN control message would otherwise reset.
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub(crate) fn encode(&self, encoder: &mut cmsg::Encoder<'_>, target: &SocketAddr, tos: u8) {
+        #[cfg(target_os = "linux")]
+        {
+            if self.src_addr.is_some() || self.interface.is_some() {
+                let v4 = match self.src_addr {
+                    Some(addr) => addr.is_ipv4(),
+                    None => target.is_ipv4(),
+                };
+                if v4 {
+                    let mut info: libc::in_pktinfo = unsafe { std::mem::zeroed() };
+                    info.ipi_ifindex = self.interface.unwrap_or(0) as c_int;
+                    if let Some(IpAddr::V4(addr)) = self.src_addr {
+                        info.ipi_spec_dst.s_addr = u32::from_ne_bytes(addr.octets());
+                    }
+                    encoder.push(libc::IPPROTO_IP, libc::IP_PKTINFO, info);
+                } else {
+                    let mut info: libc::in6_pktinfo = unsafe { std::mem::zeroed() };
+                    info.ipi6_ifindex = self.interface.unwrap_or(0);
+                    if let Some(IpAddr::V6(addr)) = self.src_addr {
+                        info.ipi6_addr.s6_addr = addr.octets();
+                    }
+                    encoder.push(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, info);
+                }
+            }
+            if let Some(ecn) = self.ecn {
+                let tos = c_int::from(tos & !0b11 | ecn.to_tos());
+                if target.is_ipv4() {
+                    encoder.push(libc::IPPROTO_IP, libc::IP_TOS, tos);
+                } else {
+                    encoder.push(libc::IPPROTO_IPV6, libc::IPV6_TCLASS, tos);
+                }
+            }
+        }
+    }
+}
+
+/// An error read from the socket error queue with `UdpSocket::recv_err`,
+/// such as an ICMP port unreachable for an earlier datagram.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct ExtendedError {
+    errno: i32,
+    origin: u8,
+    icmp_type: u8,
+    icmp_code: u8,
+    offender: Option<SocketAddr>,
+    dst_addr: SocketAddr,
+    len: usize,
+}
+
+#[cfg(target_os = "linux")]
+impl ExtendedError {
+    pub(crate) fn from_control(
+        control: &[u8],
+        dst_addr: SocketAddr,
+        len: usize,
+    ) -> Option<ExtendedError> {
+        cmsg::iter(control)
+            .find(|cmsg| {
+                matches!(
+                    (cmsg.level, cmsg.ty),
+                    (libc::IPPROTO_IP, libc::IP_RECVERR) | (libc::IPPROTO_IPV6, libc::IPV6_RECVERR)
+                )
+            })
+            .and_then(|cmsg| {
+                let err = cmsg.read::<libc::sock_extended_err>()?;
+                // The address of the node that reported the error follows
+                // the error itself.
+                let rest = &cmsg.data[mem::size_of::<libc::sock_extended_err>()..];
+                let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+                let addr_len = rest.len().min(mem::size_of::<libc::sockaddr_storage>());
+                unsafe {
+                    ptr::copy_nonoverlapping(
+                        rest.as_ptr(),
+                        &mut storage as *mut _ as *mut u8,
+                        addr_len,
+                    );
+                }
+                Some(ExtendedError {
+                    errno: err.ee_errno as i32,
+                    origin: err.ee_origin,
+                    icmp_type: err.ee_type,
+                    icmp_code: err.ee_code,
+                    offender: sys::to_socket_addr(&storage, addr_len as libc::socklen_t).ok(),
+                    dst_addr,
+                    len,
+                })
+            })
+    }
+
+    /// Returns the error, for example `ConnectionRefused` for an ICMP port
+    /// unreachable.
+    pub fn error(&self) -> io::Error {
+        io::Error::from_raw_os_error(self.errno)
+    }
+
+    /// Returns true if the error was reported by an ICMP or ICMPv6 message.
+    pub fn is_icmp(&self) -> bool {
+        self.origin == libc::SO_EE_ORIGIN_ICMP || self.origin == libc::SO_EE_ORIGIN_ICMP6
+    }
+
+    /// Returns the raw origin of the error (`SO_EE_ORIGIN_*`).
+    pub fn origin(&self) -> u8 {
+        self.origin
+    }
+
+    /// Returns the ICMP type, if `is_icmp` is true.
+    pub fn icmp_type(&self) -> u8 {
+        self.icmp_type
+    }
+
+    /// Returns the ICMP code, if `is_icmp` is true.
+    pub fn icmp_code(&self) -> u8 {
+        self.icmp_code
+    }
+
+    /// Returns the address of the node that reported the error.
+    pub fn offender(&self) -> Option<SocketAddr> {
+        self.offender
+    }
+
+    /// Returns the destination of the datagram that caused the error.
+    pub fn dst_addr(&self) -> SocketAddr {
+        self.dst_addr
+    }
+
+    /// Returns the number of bytes of the offending datagram copied into the
+    /// buffer passed to `recv_err`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
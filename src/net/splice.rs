@@ -0,0 +1,128 @@
+use std::fmt;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+
+use crate::sys::cvt;
+
+/// Moves data between two non-blocking sockets with `splice(2)`, without
+/// copying it through userspace.
+///
+/// Data is moved through an internal pipe. When the destination cannot
+/// accept more data, whatever was already read from the source stays in the
+/// pipe and is flushed first by the next call to `forward`, so a caller
+/// waiting for the destination to become writable never strands data.
+pub struct Splice {
+    reader: RawFd,
+    writer: RawFd,
+    capacity: usize,
+    pending: usize,
+}
+
+impl Splice {
+    pub fn new() -> io::Result<Splice> {
+        let mut fds = [0; 2];
+        cvt(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) })?;
+        let mut splice = Splice {
+            reader: fds[0],
+            writer: fds[1],
+            capacity: 0,
+            pending: 0,
+        };
+        splice.capacity = cvt(unsafe { libc::fcntl(splice.writer, libc::F_GETPIPE_SZ) })? as usize;
+        Ok(splice)
+    }
+
+    /// Resizes the internal pipe, returning the capacity actually used.
+    pub fn set_capacity(&mut self, size: usize) -> io::Result<usize> {
+        let size =
+            cvt(unsafe { libc::fcntl(self.writer, libc::F_SETPIPE_SZ, size as libc::c_int) })?;
+        self.capacity = size as usize;
+        Ok(self.capacity)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes read from the source that have not been
+    /// written to the destination yet.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Moves as much data as possible from `from` to `to`.
+    ///
+    /// Returns the number of bytes written to `to`. `Ok(0)` means `from`
+    /// reached end of file and nothing is left in the pipe. `WouldBlock` is
+    /// returned when no progress could be made: if `pending` is non-zero the
+    /// destination is full and the caller should wait for it to become
+    /// writable, otherwise it should wait for the source to become readable.
+    pub fn forward<R, W>(&mut self, from: &R, to: &W) -> io::Result<usize>
+    where
+        R: AsRawFd + ?Sized,
+        W: AsRawFd + ?Sized,
+    {
+        let mut moved = 0;
+
+        loop {
+            while self.pending > 0 {
+                match splice(self.reader, to.as_raw_fd(), self.pending) {
+                    Ok(n) => {
+                        self.pending -= n;
+                        moved += n;
+                    }
+                    Err(e) => return progress(moved, e),
+                }
+            }
+
+            match splice(from.as_raw_fd(), self.writer, self.capacity) {
+                Ok(0) => return Ok(moved),
+                Ok(n) => self.pending += n,
+                Err(e) => return progress(moved, e),
+            }
+        }
+    }
+}
+
+fn splice(from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+    cvt(unsafe {
+        libc::splice(
+            from,
+            ptr::null_mut(),
+            to,
+            ptr::null_mut(),
+            len,
+            libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK,
+        )
+    })
+    .map(|n| n as usize)
+}
+
+// Reports partial progress before an error; the error will show up again
+// on the next call if it was not transient.
+fn progress(moved: usize, err: io::Error) -> io::Result<usize> {
+    if moved > 0 {
+        Ok(moved)
+    } else {
+        Err(err)
+    }
+}
+
+impl Drop for Splice {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.reader);
+            libc::close(self.writer);
+        }
+    }
+}
+
+impl fmt::Debug for Splice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Splice")
+            .field("capacity", &self.capacity)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
@@ -1,4 +1,6 @@
 use std::fmt;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use std::fs::File;
 use std::io;
 use std::io::{IoSlice, IoSliceMut};
 use std::io::{Read, Write};
@@ -7,7 +9,11 @@ use std::time::Duration;
 
 use iovec::IoVec;
 
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use crate::net::MsgFlags;
 use crate::poll::convert_ready_to_interests;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use crate::sys;
 use mio::event::Source;
 
 pub struct TcpStream(mio::net::TcpStream);
@@ -27,6 +33,47 @@ impl TcpStream {
         Ok(TcpStream(mio::net::TcpStream::from_stream(stream)?))
     }
 
+    /// Starts connecting to `addr` and tries to carry `data` in the SYN
+    /// using TCP Fast Open.
+    ///
+    /// Returns the new stream and how much of `data` was sent. Like
+    /// `connect`, the connection is in progress when this returns and the
+    /// stream becomes writable once it is established. If the kernel has no
+    /// Fast Open cookie for the peer yet, or client Fast Open is disabled,
+    /// nothing is sent and the remaining data must be written after the
+    /// writable event as with a plain `connect`.
+    #[cfg(target_os = "linux")]
+    pub fn connect_fastopen(addr: &SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+        let fd = sys::socket(addr, libc::SOCK_STREAM)?;
+        let stream = unsafe { TcpStream::from_raw_fd(fd) };
+        let (raw, len) = sys::socket_addr(addr);
+
+        let res = sys::cvt(unsafe {
+            libc::sendto(
+                fd,
+                data.as_ptr() as *const libc::c_void,
+                data.len(),
+                libc::MSG_FASTOPEN | libc::MSG_NOSIGNAL,
+                &raw as *const _ as *const libc::sockaddr,
+                len,
+            )
+        });
+        match res {
+            Ok(n) => return Ok((stream, n as usize)),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => return Ok((stream, 0)),
+            Err(ref e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => {}
+            Err(e) => return Err(e),
+        }
+
+        // Fast Open was refused, fall back to a regular non-blocking connect.
+        match sys::cvt(unsafe { libc::connect(fd, &raw as *const _ as *const libc::sockaddr, len) })
+        {
+            Ok(_) => Ok((stream, 0)),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok((stream, 0)),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
         self.0.peer_addr()
     }
@@ -141,6 +188,40 @@ impl TcpStream {
         });
         unsafe { (*(&self.0 as *const _ as *mut mio::net::TcpStream)).write_vectored(&ioslices) }
     }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn send_with_flags(&self, buf: &[u8], flags: MsgFlags) -> io::Result<usize> {
+        sys::send(self.as_raw_fd(), buf, flags.bits())
+    }
+
+    /// Receives into `buf` with `flags`. With `MsgFlags::trunc()` up to
+    /// `buf.len()` bytes are discarded instead of copied, and the number
+    /// discarded is returned.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn recv_with_flags(&self, buf: &mut [u8], flags: MsgFlags) -> io::Result<usize> {
+        sys::recv(self.as_raw_fd(), buf, flags.bits())
+    }
+
+    /// Sends up to `len` bytes of `file`, starting at `offset`, without
+    /// copying them through userspace.
+    ///
+    /// Returns the number of bytes sent, which may be less than `len`, and
+    /// `Ok(0)` once `offset` is at or past the end of the file, so a loop
+    /// sending `len` bytes must stop there. When the socket buffer is full
+    /// `WouldBlock` is returned and the transfer can be resumed on the next
+    /// writable event. The file's own position is not changed.
+    #[cfg(target_os = "linux")]
+    pub fn sendfile(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        if offset > libc::off64_t::MAX as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "offset is too large",
+            ));
+        }
+        let mut offset = offset as libc::off64_t;
+        sys::cvt(unsafe { libc::sendfile64(self.as_raw_fd(), file.as_raw_fd(), &mut offset, len) })
+            .map(|n| n as usize)
+    }
 }
 
 impl Read for TcpStream {
@@ -187,7 +268,7 @@ impl crate::Evented for TcpStream {
         self.0.register(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -202,7 +283,7 @@ impl crate::Evented for TcpStream {
         self.0.reregister(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -244,6 +325,79 @@ impl TcpListener {
         self.0.accept_std()
     }
 
+    /// Accepts up to `max` pending connections, appending them to `conns`.
+    ///
+    /// Returns the number of connections accepted. The listener has been
+    /// drained when fewer than `max` were accepted; `WouldBlock` is returned
+    /// if there was nothing to accept at all. Any other error is returned
+    /// right away, and the connections accepted before it are left in
+    /// `conns`.
+    pub fn accept_many(
+        &self,
+        conns: &mut Vec<(TcpStream, SocketAddr)>,
+        max: usize,
+    ) -> io::Result<usize> {
+        self.accept_many_inner(conns, max, |_, err| Err(err))
+    }
+
+    /// Like `accept_many`, but keeps going when the process is out of file
+    /// descriptors.
+    ///
+    /// On `EMFILE` or `ENFILE` the descriptor held by `reserve` is released
+    /// to accept the pending connection and close it right away, so the
+    /// listener is still drained and an edge-triggered registration keeps
+    /// firing for new connections. Shed connections count as progress:
+    /// `Ok(0)` is returned when connections were shed but none accepted, and
+    /// `WouldBlock` only when there was nothing pending at all.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn accept_many_with_reserve(
+        &self,
+        conns: &mut Vec<(TcpStream, SocketAddr)>,
+        max: usize,
+        reserve: &mut FdReserve,
+    ) -> io::Result<usize> {
+        self.accept_many_inner(conns, max, |listener, err| {
+            if FdReserve::is_exhausted(&err) {
+                reserve.shed(listener)
+            } else {
+                Err(err)
+            }
+        })
+    }
+
+    // `on_error` returns whether accepting should go on.
+    fn accept_many_inner<F>(
+        &self,
+        conns: &mut Vec<(TcpStream, SocketAddr)>,
+        max: usize,
+        mut on_error: F,
+    ) -> io::Result<usize>
+    where
+        F: FnMut(&TcpListener, io::Error) -> io::Result<bool>,
+    {
+        let mut accepted = 0;
+        let mut shed = false;
+        while accepted < max {
+            match self.accept() {
+                Ok(conn) => {
+                    conns.push(conn);
+                    accepted += 1;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::ConnectionAborted => {}
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => match on_error(self, e) {
+                    Ok(true) => shed = true,
+                    Ok(false) => break,
+                    Err(e) => return Err(e),
+                },
+            }
+        }
+        if accepted == 0 && !shed && max > 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(accepted)
+    }
+
     /// Returns the local socket address of this listener.
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         self.0.local_addr()
@@ -272,6 +426,24 @@ impl TcpListener {
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.0.take_error()
     }
+
+    /// Enables TCP Fast Open on this listener, allowing up to `queue`
+    /// pending Fast Open requests. A `queue` of zero disables it.
+    #[cfg(target_os = "linux")]
+    pub fn set_fastopen(&self, queue: u32) -> io::Result<()> {
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN,
+            queue as libc::c_int,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn fastopen(&self) -> io::Result<u32> {
+        sys::getsockopt::<libc::c_int>(self.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_FASTOPEN)
+            .map(|queue| queue as u32)
+    }
 }
 
 impl crate::Evented for TcpListener {
@@ -286,7 +458,7 @@ impl crate::Evented for TcpListener {
         self.0.register(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -301,7 +473,7 @@ impl crate::Evented for TcpListener {
         self.0.reregister(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -317,6 +489,67 @@ impl fmt::Debug for TcpListener {
     }
 }
 
+/// A spare file descriptor kept to accept and shed connections when the
+/// process runs out of descriptors. See
+/// `TcpListener::accept_many_with_reserve`.
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+#[derive(Debug)]
+pub struct FdReserve {
+    file: Option<File>,
+    dropped: usize,
+}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+impl FdReserve {
+    pub fn new() -> io::Result<FdReserve> {
+        Ok(FdReserve {
+            file: Some(FdReserve::open()?),
+            dropped: 0,
+        })
+    }
+
+    /// Returns the number of connections closed because the process was out
+    /// of file descriptors.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    fn open() -> io::Result<File> {
+        File::open("/dev/null")
+    }
+
+    fn is_exhausted(err: &io::Error) -> bool {
+        matches!(err.raw_os_error(), Some(libc::EMFILE) | Some(libc::ENFILE))
+    }
+
+    // Frees the reserved descriptor, accepts one connection and closes it.
+    // Returns `false` once there is nothing left to accept.
+    fn shed(&mut self, listener: &TcpListener) -> io::Result<bool> {
+        let file = match self.file.take() {
+            Some(file) => file,
+            // An earlier reopen failed, so there is nothing to free. Report
+            // why the reserve cannot be reopened, or hold it again and let
+            // the caller retry the accept.
+            None => {
+                self.file = Some(FdReserve::open()?);
+                return Ok(true);
+            }
+        };
+        drop(file);
+        let accepted = match listener.accept_std() {
+            Ok(conn) => {
+                drop(conn);
+                self.dropped += 1;
+                true
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => false,
+            Err(e) => return Err(e),
+        };
+        self.file = Some(FdReserve::open()?);
+        Ok(accepted)
+    }
+}
+
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
@@ -1,22 +1,57 @@
 use std::fmt;
 use std::io;
 use std::net::{self, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicU16, AtomicU8, Ordering};
 
+#[cfg(target_os = "linux")]
+use crate::net::ExtendedError;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use crate::net::{MsgFlags, RecvMeta, SendMeta};
 use crate::poll::convert_ready_to_interests;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use crate::sys::{self, cmsg};
 use mio::event::Source;
 
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 use iovec::IoVec;
 
-pub struct UdpSocket(mio::net::UdpSocket);
+pub struct UdpSocket(mio::net::UdpSocket, Cache);
+
+// Socket properties looked up once and reused by every send.
+#[derive(Default)]
+struct Cache {
+    #[cfg(target_os = "linux")]
+    segmentation: AtomicU8,
+    // `TOS_KNOWN` plus the option value, or zero until it is read.
+    #[cfg(target_os = "linux")]
+    tos: AtomicU16,
+    #[cfg(target_os = "linux")]
+    tclass: AtomicU16,
+}
+
+#[cfg(target_os = "linux")]
+const UNKNOWN: u8 = 0;
+#[cfg(target_os = "linux")]
+const UNSUPPORTED: u8 = 1;
+#[cfg(target_os = "linux")]
+const SUPPORTED: u8 = 2;
+#[cfg(target_os = "linux")]
+const TOS_KNOWN: u16 = 0x100;
 
 impl UdpSocket {
     pub fn bind(addr: &SocketAddr) -> io::Result<UdpSocket> {
-        Ok(UdpSocket(mio::net::UdpSocket::bind(*addr)?))
+        Ok(UdpSocket(
+            mio::net::UdpSocket::bind(*addr)?,
+            Cache::default(),
+        ))
     }
 
     pub fn from_socket(socket: net::UdpSocket) -> io::Result<UdpSocket> {
-        Ok(UdpSocket(mio::net::UdpSocket::from_socket(socket)?))
+        Ok(UdpSocket(
+            mio::net::UdpSocket::from_socket(socket)?,
+            Cache::default(),
+        ))
     }
 
     #[cfg_attr(not(target_os = "freebsd"), doc = " ```")]
@@ -26,7 +61,7 @@ impl UdpSocket {
     }
 
     pub fn try_clone(&self) -> io::Result<UdpSocket> {
-        Ok(UdpSocket(self.0.try_clone()?))
+        Ok(UdpSocket(self.0.try_clone()?, Cache::default()))
     }
 
     pub fn send_to(&self, buf: &[u8], target: &SocketAddr) -> io::Result<usize> {
@@ -37,6 +72,30 @@ impl UdpSocket {
         self.0.recv_from(buf)
     }
 
+    /// Receives the next datagram without removing it from the queue.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_with_flags(buf, MsgFlags::peek())
+    }
+
+    /// Like `peek`, but also returns the address the datagram came from.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let mut iov = [libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        }];
+        let msg = sys::recvmsg(self.as_raw_fd(), &mut iov, &mut [], MsgFlags::peek().bits())?;
+        Ok((msg.len, msg.socket_addr()?))
+    }
+
+    /// Returns the full length of the next queued datagram without consuming
+    /// it (`MSG_PEEK | MSG_TRUNC`).
+    #[cfg(target_os = "linux")]
+    pub fn peek_len(&self) -> io::Result<usize> {
+        self.recv_with_flags(&mut [], MsgFlags::peek() | MsgFlags::trunc())
+    }
+
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
         self.0.send(buf)
     }
@@ -49,6 +108,21 @@ impl UdpSocket {
         self.0.connect(addr)
     }
 
+    /// Dissolves the association set up by `connect`, so that datagrams from
+    /// any peer are received again.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn disconnect(&self) -> io::Result<()> {
+        let mut addr: libc::sockaddr = unsafe { std::mem::zeroed() };
+        addr.sa_family = libc::AF_UNSPEC as libc::sa_family_t;
+        let len = std::mem::size_of::<libc::sockaddr>() as libc::socklen_t;
+        match sys::cvt(unsafe { libc::connect(self.as_raw_fd(), &addr, len) }) {
+            Ok(_) => Ok(()),
+            // The BSDs dissolve the association but still report an error.
+            Err(ref e) if e.raw_os_error() == Some(libc::EAFNOSUPPORT) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
         self.0.set_broadcast(on)
     }
@@ -105,6 +179,181 @@ impl UdpSocket {
         self.0.leave_multicast_v6(multiaddr, interface)
     }
 
+    /// Sets the local interface address used for outgoing IPv4 multicast
+    /// (`IP_MULTICAST_IF`).
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> io::Result<()> {
+        let addr = libc::in_addr {
+            s_addr: u32::from_ne_bytes(interface.octets()),
+        };
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_MULTICAST_IF,
+            addr,
+        )
+    }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn multicast_if_v4(&self) -> io::Result<Ipv4Addr> {
+        let addr: libc::in_addr =
+            sys::getsockopt(self.as_raw_fd(), libc::IPPROTO_IP, libc::IP_MULTICAST_IF)?;
+        Ok(Ipv4Addr::from(addr.s_addr.to_ne_bytes()))
+    }
+
+    /// Sets the index of the interface used for outgoing IPv6 multicast
+    /// (`IPV6_MULTICAST_IF`), 0 lets the kernel choose.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        let interface = interface as libc::c_int;
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_IF,
+            interface,
+        )
+    }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn multicast_if_v6(&self) -> io::Result<u32> {
+        let index: libc::c_int = sys::getsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_IF,
+        )?;
+        Ok(index as u32)
+    }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        let hops = hops as libc::c_int;
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_HOPS,
+            hops,
+        )
+    }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        let hops: libc::c_int = sys::getsockopt(
+            self.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_HOPS,
+        )?;
+        Ok(hops as u32)
+    }
+
+    /// Joins an IPv4 multicast group on the interface with the given index
+    /// rather than by address (`ip_mreqn`).
+    #[cfg(target_os = "linux")]
+    pub fn join_multicast_v4_index(&self, multiaddr: &Ipv4Addr, interface: u32) -> io::Result<()> {
+        self.set_mreqn(libc::IP_ADD_MEMBERSHIP, multiaddr, interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn leave_multicast_v4_index(&self, multiaddr: &Ipv4Addr, interface: u32) -> io::Result<()> {
+        self.set_mreqn(libc::IP_DROP_MEMBERSHIP, multiaddr, interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_mreqn(&self, opt: libc::c_int, multiaddr: &Ipv4Addr, interface: u32) -> io::Result<()> {
+        let mreq = libc::ip_mreqn {
+            imr_multiaddr: libc::in_addr {
+                s_addr: u32::from_ne_bytes(multiaddr.octets()),
+            },
+            imr_address: libc::in_addr { s_addr: 0 },
+            imr_ifindex: interface as libc::c_int,
+        };
+        sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IP, opt, mreq)
+    }
+
+    /// Joins a source-specific IPv4 multicast group, receiving only datagrams
+    /// sent by `source` (`IP_ADD_SOURCE_MEMBERSHIP`).
+    #[cfg(target_os = "linux")]
+    pub fn join_source_multicast_v4(
+        &self,
+        source: &Ipv4Addr,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        self.set_mreq_source(libc::IP_ADD_SOURCE_MEMBERSHIP, source, multiaddr, interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn leave_source_multicast_v4(
+        &self,
+        source: &Ipv4Addr,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        self.set_mreq_source(
+            libc::IP_DROP_SOURCE_MEMBERSHIP,
+            source,
+            multiaddr,
+            interface,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_mreq_source(
+        &self,
+        opt: libc::c_int,
+        source: &Ipv4Addr,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        let in_addr = |addr: &Ipv4Addr| libc::in_addr {
+            s_addr: u32::from_ne_bytes(addr.octets()),
+        };
+        let mreq = libc::ip_mreq_source {
+            imr_multiaddr: in_addr(multiaddr),
+            imr_interface: in_addr(interface),
+            imr_sourceaddr: in_addr(source),
+        };
+        sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IP, opt, mreq)
+    }
+
+    /// Joins a source-specific IPv6 multicast group, receiving only datagrams
+    /// sent by `source` (`MCAST_JOIN_SOURCE_GROUP`).
+    #[cfg(target_os = "linux")]
+    pub fn join_source_multicast_v6(
+        &self,
+        source: &Ipv6Addr,
+        multiaddr: &Ipv6Addr,
+        interface: u32,
+    ) -> io::Result<()> {
+        self.set_group_source_req(libc::MCAST_JOIN_SOURCE_GROUP, source, multiaddr, interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn leave_source_multicast_v6(
+        &self,
+        source: &Ipv6Addr,
+        multiaddr: &Ipv6Addr,
+        interface: u32,
+    ) -> io::Result<()> {
+        self.set_group_source_req(libc::MCAST_LEAVE_SOURCE_GROUP, source, multiaddr, interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_group_source_req(
+        &self,
+        opt: libc::c_int,
+        source: &Ipv6Addr,
+        multiaddr: &Ipv6Addr,
+        interface: u32,
+    ) -> io::Result<()> {
+        let storage = |addr: &Ipv6Addr| sys::socket_addr(&SocketAddr::new((*addr).into(), 0)).0;
+        let req = libc::group_source_req {
+            gsr_interface: interface,
+            gsr_group: storage(multiaddr),
+            gsr_source: storage(source),
+        };
+        sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IPV6, opt, req)
+    }
+
     pub fn set_only_v6(&self, _only_v6: bool) -> io::Result<()> {
         unreachable!();
     }
@@ -117,33 +366,399 @@ impl UdpSocket {
         self.0.take_error()
     }
 
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn send_with_flags(&self, buf: &[u8], flags: MsgFlags) -> io::Result<usize> {
+        sys::send(self.as_raw_fd(), buf, flags.bits())
+    }
+
+    /// Receives a datagram into `buf` with `flags`. With `MsgFlags::trunc()`
+    /// the full length of the datagram is returned, which can exceed
+    /// `buf.len()`; only `buf.len()` bytes are copied.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn recv_with_flags(&self, buf: &mut [u8], flags: MsgFlags) -> io::Result<usize> {
+        sys::recv(self.as_raw_fd(), buf, flags.bits())
+    }
+
     #[cfg(all(unix, not(target_os = "fuchsia")))]
     pub fn recv_bufs(&self, bufs: &mut [&mut IoVec]) -> io::Result<usize> {
-        let size = bufs.iter().map(|buf| buf.len()).sum();
-        let mut buf = vec![0; size];
-        let size = self.recv(&mut buf)?;
-        let mut buf_i = 0;
-        for b in bufs.iter_mut() {
-            for b_i in 0..(b.len()) {
-                if buf_i >= size {
-                    break;
-                }
-                b[b_i] = buf[buf_i];
-                buf_i += 1;
-            }
-            if buf_i >= size {
-                break;
-            }
+        self.recv_from_bufs(bufs).map(|meta| meta.len())
+    }
+
+    /// Receives a single datagram into `bufs`, filling them in order.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn recv_from_bufs(&self, bufs: &mut [&mut IoVec]) -> io::Result<RecvMeta> {
+        self.recv_meta(iovec::unix::as_os_slice_mut(bufs))
+    }
+
+    /// Receives a single datagram along with the information carried by its
+    /// control messages, such as the address it was sent to when
+    /// `set_recv_pktinfo` is enabled.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn recv_msg(&self, buf: &mut [u8]) -> io::Result<RecvMeta> {
+        self.recv_meta(&mut [libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        }])
+    }
+
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    fn recv_meta(&self, iov: &mut [libc::iovec]) -> io::Result<RecvMeta> {
+        let mut control = cmsg::ControlBuf::new();
+        let msg = sys::recvmsg(
+            self.as_raw_fd(),
+            iov,
+            control.as_mut(),
+            MsgFlags::empty().bits(),
+        )?;
+        Ok(RecvMeta::new(msg.len, msg.socket_addr()?, msg.flags)
+            .with_control(&control.as_ref()[..msg.control_len]))
+    }
+
+    /// Sends `buf` to `target` with the per-datagram options of `meta`, for
+    /// example to pin the source address of a reply.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn send_msg(&self, buf: &[u8], target: &SocketAddr, meta: &SendMeta) -> io::Result<usize> {
+        #[cfg(target_os = "linux")]
+        let tos = if meta.has_ecn() {
+            self.cached_tos(target)?
+        } else {
+            0
+        };
+        #[cfg(not(target_os = "linux"))]
+        let tos = 0;
+        let mut control = cmsg::ControlBuf::new();
+        let mut encoder = cmsg::Encoder::new(control.as_mut());
+        meta.encode(&mut encoder, target, tos);
+        let iov = [libc::iovec {
+            iov_base: buf.as_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        }];
+        sys::sendmsg(
+            self.as_raw_fd(),
+            &iov,
+            Some(target),
+            encoder.finish(),
+            MsgFlags::empty().bits(),
+        )
+    }
+
+    /// Enables reporting the destination address and interface of received
+    /// datagrams in `RecvMeta` (`IP_PKTINFO` or `IPV6_RECVPKTINFO`).
+    #[cfg(target_os = "linux")]
+    pub fn set_recv_pktinfo(&self, on: bool) -> io::Result<()> {
+        self.set_recv_option(libc::IP_PKTINFO, libc::IPV6_RECVPKTINFO, on)
+    }
+
+    /// Queues ICMP and local errors for sent datagrams on the socket error
+    /// queue (`IP_RECVERR` or `IPV6_RECVERR`). They are signalled as
+    /// `UnixReady::error` readiness and read with `recv_err`.
+    #[cfg(target_os = "linux")]
+    pub fn set_recv_err(&self, on: bool) -> io::Result<()> {
+        self.set_recv_option(libc::IP_RECVERR, libc::IPV6_RECVERR, on)
+    }
+
+    /// Reads the next error from the socket error queue (`MSG_ERRQUEUE`),
+    /// copying the start of the offending datagram into `buf`.
+    #[cfg(target_os = "linux")]
+    pub fn recv_err(&self, buf: &mut [u8]) -> io::Result<ExtendedError> {
+        let mut control = cmsg::ControlBuf::new();
+        let mut iov = [libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        }];
+        let flags = MsgFlags::empty().bits() | libc::MSG_ERRQUEUE;
+        let msg = sys::recvmsg(self.as_raw_fd(), &mut iov, control.as_mut(), flags)?;
+        ExtendedError::from_control(
+            &control.as_ref()[..msg.control_len],
+            msg.socket_addr()?,
+            msg.len,
+        )
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing extended error"))
+    }
+
+    /// Enables kernel receive timestamps (`SO_TIMESTAMPNS`), reported by
+    /// `RecvMeta::timestamp`.
+    #[cfg(target_os = "linux")]
+    pub fn set_recv_timestamp(&self, on: bool) -> io::Result<()> {
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMPNS,
+            on as libc::c_int,
+        )
+    }
+
+    /// Enables reporting the type of service or traffic class of received
+    /// datagrams (`IP_RECVTOS` or `IPV6_RECVTCLASS`), see `RecvMeta::tos` and
+    /// `RecvMeta::ecn`.
+    #[cfg(target_os = "linux")]
+    pub fn set_recv_tos(&self, on: bool) -> io::Result<()> {
+        self.set_recv_option(libc::IP_RECVTOS, libc::IPV6_RECVTCLASS, on)
+    }
+
+    /// Sets the type of service or traffic class of sent datagrams (`IP_TOS`,
+    /// and `IPV6_TCLASS` on IPv6 sockets). `send_msg` keeps its DSCP bits
+    /// when marking a datagram with `SendMeta::ecn`; a value set through
+    /// the raw fd after the first such send is not seen.
+    #[cfg(target_os = "linux")]
+    pub fn set_tos(&self, tos: u8) -> io::Result<()> {
+        let value = libc::c_int::from(tos);
+        sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IP, libc::IP_TOS, value)?;
+        self.1
+            .tos
+            .store(TOS_KNOWN | u16::from(tos), Ordering::Relaxed);
+        if self.local_addr()?.is_ipv6() {
+            sys::setsockopt(
+                self.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_TCLASS,
+                value,
+            )?;
+            self.1
+                .tclass
+                .store(TOS_KNOWN | u16::from(tos), Ordering::Relaxed);
         }
-        Ok(buf_i)
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn cached_tos(&self, target: &SocketAddr) -> io::Result<u8> {
+        let (cache, level, name) = if target.is_ipv4() {
+            (&self.1.tos, libc::IPPROTO_IP, libc::IP_TOS)
+        } else {
+            (&self.1.tclass, libc::IPPROTO_IPV6, libc::IPV6_TCLASS)
+        };
+        let cached = cache.load(Ordering::Relaxed);
+        if cached & TOS_KNOWN != 0 {
+            return Ok(cached as u8);
+        }
+        let tos = sys::getsockopt::<libc::c_int>(self.as_raw_fd(), level, name)? as u8;
+        cache.store(TOS_KNOWN | u16::from(tos), Ordering::Relaxed);
+        Ok(tos)
+    }
+
+    /// Enables reporting the time to live or hop limit of received datagrams
+    /// (`IP_RECVTTL` or `IPV6_RECVHOPLIMIT`), see `RecvMeta::ttl`.
+    #[cfg(target_os = "linux")]
+    pub fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        self.set_recv_option(libc::IP_RECVTTL, libc::IPV6_RECVHOPLIMIT, on)
+    }
+
+    // IPv6 sockets get the IPv4 option as well, for the IPv4-mapped traffic
+    // they may receive.
+    #[cfg(target_os = "linux")]
+    fn set_recv_option(&self, v4: libc::c_int, v6: libc::c_int, on: bool) -> io::Result<()> {
+        let on = on as libc::c_int;
+        sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IP, v4, on)?;
+        if self.local_addr()?.is_ipv6() {
+            sys::setsockopt(self.as_raw_fd(), libc::IPPROTO_IPV6, v6, on)?;
+        }
+        Ok(())
     }
 
     #[cfg(all(unix, not(target_os = "fuchsia")))]
     pub fn send_bufs(&self, bufs: &[&IoVec]) -> io::Result<usize> {
-        let size = bufs.iter().map(|buf| buf.len()).sum();
-        let mut buf = Vec::with_capacity(size);
-        bufs.iter().for_each(|b| buf.extend_from_slice(&b));
-        self.send(&buf)
+        sys::sendmsg(
+            self.as_raw_fd(),
+            iovec::unix::as_os_slice(bufs),
+            None,
+            &[],
+            MsgFlags::empty().bits(),
+        )
+    }
+
+    /// Sends the concatenation of `bufs` as a single datagram to `target`.
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    pub fn send_to_bufs(&self, bufs: &[&IoVec], target: &SocketAddr) -> io::Result<usize> {
+        sys::sendmsg(
+            self.as_raw_fd(),
+            iovec::unix::as_os_slice(bufs),
+            Some(target),
+            &[],
+            MsgFlags::empty().bits(),
+        )
+    }
+
+    /// Receives up to `bufs.len()` datagrams with a single system call.
+    ///
+    /// `meta` is cleared and then holds one entry per received datagram,
+    /// the `n`th one describing what was written to `bufs[n]`. Returns the
+    /// number of datagrams received, or `WouldBlock` if none were queued.
+    #[cfg(target_os = "linux")]
+    pub fn recv_many(&self, bufs: &mut [&mut [u8]], meta: &mut Vec<RecvMeta>) -> io::Result<usize> {
+        meta.clear();
+        sys::recvmmsg(
+            self.as_raw_fd(),
+            bufs,
+            MsgFlags::empty().bits(),
+            |msg, control| {
+                meta.push(
+                    RecvMeta::new(msg.len, msg.socket_addr()?, msg.flags).with_control(control),
+                );
+                Ok(())
+            },
+        )
+    }
+
+    /// Sends each of `bufs` as a datagram to the connected peer with a single
+    /// system call.
+    ///
+    /// Returns the number of datagrams sent, which is less than `bufs.len()`
+    /// when the socket buffer filled up. `WouldBlock` is returned if nothing
+    /// could be sent.
+    #[cfg(target_os = "linux")]
+    pub fn send_many(&self, bufs: &[&[u8]]) -> io::Result<usize> {
+        sys::sendmmsg(self.as_raw_fd(), bufs, None, MsgFlags::empty().bits())
+    }
+
+    /// Like `send_many`, but sends `bufs[n]` to `targets[n]`.
+    #[cfg(target_os = "linux")]
+    pub fn send_to_many(&self, bufs: &[&[u8]], targets: &[SocketAddr]) -> io::Result<usize> {
+        if bufs.len() != targets.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "every buffer needs a target address",
+            ));
+        }
+        sys::sendmmsg(
+            self.as_raw_fd(),
+            bufs,
+            Some(targets),
+            MsgFlags::empty().bits(),
+        )
+    }
+
+    /// Sets the segment size used by UDP segmentation offload for every
+    /// send on this socket (`UDP_SEGMENT`). Zero disables it.
+    #[cfg(target_os = "linux")]
+    pub fn set_segment_size(&self, size: u16) -> io::Result<()> {
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_UDP,
+            libc::UDP_SEGMENT,
+            libc::c_int::from(size),
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn segment_size(&self) -> io::Result<u16> {
+        sys::getsockopt::<libc::c_int>(self.as_raw_fd(), libc::SOL_UDP, libc::UDP_SEGMENT)
+            .map(|size| size as u16)
+    }
+
+    /// Returns whether the kernel supports UDP segmentation offload. When
+    /// it does not, `send_segments` falls back to one system call per
+    /// segment.
+    #[cfg(target_os = "linux")]
+    pub fn supports_segmentation(&self) -> bool {
+        match self.1.segmentation.load(Ordering::Relaxed) {
+            UNKNOWN => {
+                // Kernels without `UDP_SEGMENT` reject the option, but would
+                // silently ignore it as a control message.
+                let supported = self.segment_size().is_ok();
+                let state = if supported { SUPPORTED } else { UNSUPPORTED };
+                self.1.segmentation.store(state, Ordering::Relaxed);
+                supported
+            }
+            state => state == SUPPORTED,
+        }
+    }
+
+    /// Sends `buf` to the connected peer as datagrams of `segment_size`
+    /// bytes each, the last one possibly shorter, using UDP segmentation
+    /// offload.
+    ///
+    /// Returns the number of bytes sent. If the kernel does not support
+    /// segmentation offload, or the device cannot checksum the segments
+    /// (`EIO`), the datagrams are sent one by one and the count may then
+    /// stop short at a segment boundary when the socket buffer fills up.
+    /// Other errors, such as `EINVAL` for a buffer split into more segments
+    /// than the kernel accepts, are returned as is.
+    #[cfg(target_os = "linux")]
+    pub fn send_segments(&self, buf: &[u8], segment_size: usize) -> io::Result<usize> {
+        self.send_segments_inner(buf, segment_size, None)
+    }
+
+    /// Like `send_segments`, but sends the datagrams to `target`.
+    #[cfg(target_os = "linux")]
+    pub fn send_to_segments(
+        &self,
+        buf: &[u8],
+        segment_size: usize,
+        target: &SocketAddr,
+    ) -> io::Result<usize> {
+        self.send_segments_inner(buf, segment_size, Some(target))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn send_segments_inner(
+        &self,
+        buf: &[u8],
+        segment_size: usize,
+        target: Option<&SocketAddr>,
+    ) -> io::Result<usize> {
+        if segment_size == 0 || segment_size > usize::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid segment size",
+            ));
+        }
+
+        if self.supports_segmentation() {
+            let mut control = cmsg::ControlBuf::new();
+            let mut encoder = cmsg::Encoder::new(control.as_mut());
+            encoder.push(libc::SOL_UDP, libc::UDP_SEGMENT, segment_size as u16);
+            let iov = [libc::iovec {
+                iov_base: buf.as_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            }];
+
+            match sys::sendmsg(
+                self.as_raw_fd(),
+                &iov,
+                target,
+                encoder.finish(),
+                MsgFlags::empty().bits(),
+            ) {
+                // The device cannot checksum the segments.
+                Err(ref e) if e.raw_os_error() == Some(libc::EIO) => {}
+                res => return res,
+            }
+        }
+
+        let mut sent = 0;
+        for segment in buf.chunks(segment_size) {
+            let res = match target {
+                Some(target) => self.send_to(segment, target),
+                None => self.send(segment),
+            };
+            match res {
+                Ok(n) => sent += n,
+                Err(e) if sent == 0 => return Err(e),
+                Err(_) => break,
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Enables UDP receive offload (`UDP_GRO`), letting the kernel hand over
+    /// several datagrams of the same size from one peer as a single one. The
+    /// segment size is reported by `RecvMeta::segment_size`. Fails on kernels
+    /// without support for it.
+    #[cfg(target_os = "linux")]
+    pub fn set_gro(&self, on: bool) -> io::Result<()> {
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_UDP,
+            libc::UDP_GRO,
+            on as libc::c_int,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn gro(&self) -> io::Result<bool> {
+        sys::getsockopt::<libc::c_int>(self.as_raw_fd(), libc::SOL_UDP, libc::UDP_GRO)
+            .map(|on| on != 0)
     }
 }
 
@@ -156,10 +771,13 @@ impl crate::Evented for UdpSocket {
         _opts: crate::PollOpt,
     ) -> io::Result<()> {
         let registry = unsafe { poll.registry() };
+        // Errors queued with `set_recv_err` are signalled this way.
+        #[cfg(all(unix, not(target_os = "fuchsia")))]
+        poll.report_unix_ready(token, crate::unix::UnixReady::error());
         self.0.register(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -171,10 +789,13 @@ impl crate::Evented for UdpSocket {
         _opts: crate::PollOpt,
     ) -> io::Result<()> {
         let registry = unsafe { poll.registry() };
+        // Errors queued with `set_recv_err` are signalled this way.
+        #[cfg(all(unix, not(target_os = "fuchsia")))]
+        poll.report_unix_ready(token, crate::unix::UnixReady::error());
         self.0.reregister(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -210,6 +831,6 @@ impl AsRawFd for UdpSocket {
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 impl FromRawFd for UdpSocket {
     unsafe fn from_raw_fd(fd: RawFd) -> UdpSocket {
-        UdpSocket(mio::net::UdpSocket::from_raw_fd(fd))
+        UdpSocket(mio::net::UdpSocket::from_raw_fd(fd), Cache::default())
     }
 }
@@ -1,21 +1,33 @@
+#[cfg(unix)]
+use std::collections::HashMap;
 use std::fmt;
 use std::io;
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(unix)]
+use std::sync::Mutex;
 use std::sync::RwLock;
 use std::time::Duration;
 
 use crate::evented::EventedSource;
 use crate::{Event, Events, PollOpt, Ready, Token};
+#[cfg(unix)]
+use mio_old::unix::UnixReady;
 
 pub struct Poll {
     poll: RwLock<mio::Poll>,
+    // `UnixReady` kinds that the source registered with a token passes on,
+    // as only some of them report errors and hang-ups like mio 0.6 did.
+    #[cfg(unix)]
+    unix_ready: Mutex<HashMap<usize, Ready>>,
 }
 
 impl Poll {
     pub fn new() -> io::Result<Poll> {
         Ok(Poll {
             poll: RwLock::new(mio::Poll::new()?),
+            #[cfg(unix)]
+            unix_ready: Mutex::new(HashMap::new()),
         })
     }
 
@@ -30,10 +42,9 @@ impl Poll {
         E: crate::Evented,
     {
         validate_args(opts)?;
-        let interests = match convert_ready_to_interests(interest) {
-            Some(interests) => interests,
-            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid Ready")),
-        };
+        let interests = convert_ready_to_interests(interest)?;
+        #[cfg(unix)]
+        self.unix_ready.lock().unwrap().remove(&token.0);
         self.poll.read().unwrap().registry().register(
             &EventedSource::new(handle, &self),
             mio::Token(token.0),
@@ -52,10 +63,9 @@ impl Poll {
         E: crate::Evented,
     {
         validate_args(opts)?;
-        let interests = match convert_ready_to_interests(interest) {
-            Some(interests) => interests,
-            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid Ready")),
-        };
+        let interests = convert_ready_to_interests(interest)?;
+        #[cfg(unix)]
+        self.unix_ready.lock().unwrap().remove(&token.0);
         self.poll.read().unwrap().registry().reregister(
             &EventedSource::new(handle, &self),
             mio::Token(token.0),
@@ -79,7 +89,7 @@ impl Poll {
         let size = self.poll.write().unwrap().poll(&mut new_events, timeout)?;
         for event in &new_events {
             events.inner.push(Event::new(
-                convert_event_to_ready(event),
+                self.convert_event_to_ready(event),
                 Token(event.token().0),
             ));
         }
@@ -100,7 +110,7 @@ impl Poll {
             .poll_interruptible(&mut new_events, timeout)?;
         for event in &new_events {
             events.inner.push(Event::new(
-                convert_event_to_ready(event),
+                self.convert_event_to_ready(event),
                 Token(event.token().0),
             ));
         }
@@ -110,6 +120,36 @@ impl Poll {
     pub(crate) unsafe fn registry(&self) -> &mio::Registry {
         &*(self.poll.read().unwrap().registry() as *const mio::Registry)
     }
+
+    // Called by a source while it is being registered with `token`.
+    #[cfg(unix)]
+    pub(crate) fn report_unix_ready(&self, token: Token, ready: UnixReady) {
+        self.unix_ready
+            .lock()
+            .unwrap()
+            .insert(token.0, Ready::from(ready));
+    }
+
+    fn convert_event_to_ready(&self, event: &mio::event::Event) -> Ready {
+        #[cfg_attr(not(unix), allow(unused_mut))]
+        let mut ready = convert_event_to_ready(event);
+        #[cfg(unix)]
+        {
+            if event.is_error() || event.is_hup() {
+                let reported = match self.unix_ready.lock().unwrap().get(&event.token().0) {
+                    Some(&reported) => UnixReady::from(reported),
+                    None => return ready,
+                };
+                if event.is_error() && reported.is_error() {
+                    ready |= UnixReady::error();
+                }
+                if event.is_hup() && reported.is_hup() {
+                    ready |= UnixReady::hup();
+                }
+            }
+        }
+        ready
+    }
 }
 
 fn validate_args(opts: PollOpt) -> io::Result<()> {
@@ -123,17 +163,17 @@ fn validate_args(opts: PollOpt) -> io::Result<()> {
     Ok(())
 }
 
-pub(crate) fn convert_ready_to_interests(ready: Ready) -> Option<mio::Interests> {
+pub(crate) fn convert_ready_to_interests(ready: Ready) -> io::Result<mio::Interests> {
     use mio::Interests;
 
     if ready.is_readable() && ready.is_writable() {
-        Some(Interests::READABLE | Interests::WRITABLE)
+        Ok(Interests::READABLE | Interests::WRITABLE)
     } else if ready.is_readable() {
-        Some(Interests::READABLE)
+        Ok(Interests::READABLE)
     } else if ready.is_writable() {
-        Some(Interests::WRITABLE)
+        Ok(Interests::WRITABLE)
     } else {
-        None
+        Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid Ready"))
     }
 }
 
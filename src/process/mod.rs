@@ -0,0 +1,193 @@
+//! Child processes with non-blocking, `Evented` standard I/O and exit
+//! notification.
+
+use std::fmt;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::process::{self, Command, ExitStatus};
+
+#[cfg(target_os = "linux")]
+use crate::unix::EventedFd;
+use crate::unix::{PipeReader, PipeWriter};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+mod sigchld;
+
+/// A spawned child process.
+///
+/// The standard I/O handles are present when the corresponding stream of
+/// the `Command` was set to `Stdio::piped()`.
+#[derive(Debug)]
+pub struct Child {
+    pub stdin: Option<PipeWriter>,
+    pub stdout: Option<PipeReader>,
+    pub stderr: Option<PipeReader>,
+    pub exit: ChildExit,
+}
+
+impl Child {
+    /// Spawns `command`, making its piped standard I/O non-blocking.
+    pub fn spawn(command: &mut Command) -> io::Result<Child> {
+        let mut child = command.spawn()?;
+        let stdin = child.stdin.take().map(PipeWriter::from_stdin);
+        let stdout = child.stdout.take().map(PipeReader::from_stdout);
+        let stderr = child.stderr.take().map(PipeReader::from_stderr);
+        let mut exit = ChildExit::new(child)?;
+        match (stdin.transpose(), stdout.transpose(), stderr.transpose()) {
+            (Ok(stdin), Ok(stdout), Ok(stderr)) => Ok(Child {
+                stdin,
+                stdout,
+                stderr,
+                exit,
+            }),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                reap(&mut exit.child);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.exit.id()
+    }
+}
+
+/// A handle to the exit of a child process, which becomes readable once the
+/// child has exited.
+///
+/// It uses a pidfd on Linux 5.3 and later, and a `SIGCHLD` handler
+/// otherwise. The handler is installed once and kept for the lifetime of
+/// the process; it calls any handler installed before it, and is shared
+/// with a `unix::Signals` receiving `SIGCHLD`. It is not installed over an
+/// ignored `SIGCHLD`, which leaves the kernel reaping children: creating
+/// the handle then fails.
+pub struct ChildExit {
+    child: process::Child,
+    notify: Notify,
+}
+
+enum Notify {
+    #[cfg(target_os = "linux")]
+    Pidfd(OwnedFd),
+    Sigchld(sigchld::Watch),
+}
+
+impl ChildExit {
+    fn new(child: process::Child) -> io::Result<ChildExit> {
+        ChildExit::with_notify(child, Notify::new)
+    }
+
+    /// Watches `child` with the `SIGCHLD` handler even where pidfds are
+    /// available.
+    #[doc(hidden)]
+    pub fn with_sigchld(child: process::Child) -> io::Result<ChildExit> {
+        ChildExit::with_notify(child, |pid| sigchld::Watch::new(pid).map(Notify::Sigchld))
+    }
+
+    fn with_notify<F>(mut child: process::Child, notify: F) -> io::Result<ChildExit>
+    where
+        F: FnOnce(libc::pid_t) -> io::Result<Notify>,
+    {
+        match notify(child.id() as libc::pid_t) {
+            Ok(notify) => Ok(ChildExit { child, notify }),
+            Err(e) => {
+                reap(&mut child);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns the exit status of the child if it has exited, or `None`
+    /// without blocking if it is still running.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Sends `SIGKILL` to the child.
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+}
+
+// Kills and waits for a child that cannot be handed to the caller, rather
+// than leave it running unobserved.
+fn reap(child: &mut process::Child) {
+    drop(child.kill());
+    drop(child.wait());
+}
+
+impl Notify {
+    #[cfg(target_os = "linux")]
+    fn new(pid: libc::pid_t) -> io::Result<Notify> {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+        if fd >= 0 {
+            return Ok(Notify::Pidfd(unsafe { OwnedFd::from_raw_fd(fd as _) }));
+        }
+        // Kernels before 5.3, or a seccomp policy, reject pidfds.
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::ENOSYS) | Some(libc::EPERM) => sigchld::Watch::new(pid).map(Notify::Sigchld),
+            _ => Err(err),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(pid: libc::pid_t) -> io::Result<Notify> {
+        sigchld::Watch::new(pid).map(Notify::Sigchld)
+    }
+}
+
+impl Evented for ChildExit {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        match self.notify {
+            #[cfg(target_os = "linux")]
+            Notify::Pidfd(ref fd) => {
+                EventedFd(&fd.as_raw_fd()).register(poll, token, interest, opts)
+            }
+            Notify::Sigchld(ref watch) => watch.register(poll, token, interest, opts),
+        }
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        match self.notify {
+            #[cfg(target_os = "linux")]
+            Notify::Pidfd(ref fd) => {
+                EventedFd(&fd.as_raw_fd()).reregister(poll, token, interest, opts)
+            }
+            Notify::Sigchld(ref watch) => watch.reregister(poll, token, interest, opts),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.notify {
+            #[cfg(target_os = "linux")]
+            Notify::Pidfd(ref fd) => EventedFd(&fd.as_raw_fd()).deregister(poll),
+            Notify::Sigchld(ref watch) => watch.deregister(poll),
+        }
+    }
+}
+
+impl fmt::Debug for ChildExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildExit")
+            .field("id", &self.child.id())
+            .finish()
+    }
+}
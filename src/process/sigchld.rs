@@ -0,0 +1,153 @@
+//! `SIGCHLD` based exit notification, for systems without pidfds.
+//!
+//! The shared signal handler writes to a self-pipe, and a helper thread
+//! reading the pipe checks every watched child with `waitid(WNOWAIT)`,
+//! which leaves the child to be reaped by `ChildExit::try_wait`, and sets
+//! the readiness of the watches whose child has exited.
+
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+use std::sync::{Mutex, Once};
+use std::thread;
+
+use crate::sys;
+use crate::sys::signal::{self, Slot};
+use crate::{Evented, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+
+static INIT: Once = Once::new();
+static INIT_ERROR: Mutex<Option<io::Error>> = Mutex::new(None);
+static WATCHED: Mutex<Vec<(libc::pid_t, SetReadiness)>> = Mutex::new(Vec::new());
+
+pub(super) struct Watch {
+    pid: libc::pid_t,
+    registration: Registration,
+}
+
+impl Watch {
+    pub(super) fn new(pid: libc::pid_t) -> io::Result<Watch> {
+        init()?;
+        let (registration, set_readiness) = Registration::new2();
+        // The child may have exited before the handler was installed.
+        if exited(pid) {
+            set_readiness.set_readiness(Ready::readable())?;
+        } else {
+            WATCHED.lock().unwrap().push((pid, set_readiness));
+            // Or between the check and the push.
+            check_watched();
+        }
+        Ok(Watch { pid, registration })
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        WATCHED.lock().unwrap().retain(|&(pid, _)| pid != self.pid);
+    }
+}
+
+impl Evented for Watch {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.registration.deregister(poll)
+    }
+}
+
+// Also true once the child was reaped.
+fn exited(pid: libc::pid_t) -> bool {
+    let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+    let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    match sys::cvt(unsafe { libc::waitid(libc::P_PID, pid as _, &mut info, flags) }) {
+        // `si_pid` stays zero while the child is running.
+        Ok(_) => unsafe { info.si_pid() != 0 },
+        Err(_) => true,
+    }
+}
+
+fn check_watched() {
+    WATCHED.lock().unwrap().retain(|&(pid, ref set_readiness)| {
+        if exited(pid) {
+            drop(set_readiness.set_readiness(Ready::readable()));
+            false
+        } else {
+            true
+        }
+    });
+}
+
+fn init() -> io::Result<()> {
+    INIT.call_once(|| {
+        if let Err(e) = install() {
+            *INIT_ERROR.lock().unwrap() = Some(e);
+        }
+    });
+    match *INIT_ERROR.lock().unwrap() {
+        Some(ref e) => match e.raw_os_error() {
+            Some(errno) => Err(io::Error::from_raw_os_error(errno)),
+            None => Err(io::Error::new(e.kind(), e.to_string())),
+        },
+        None => Ok(()),
+    }
+}
+
+fn install() -> io::Result<()> {
+    // Children are then reaped by the kernel as they exit, which installing
+    // a handler would stop for the whole process.
+    if sigchld_ignored() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "SIGCHLD is ignored",
+        ));
+    }
+
+    let (read, write) = crate::unix::pipe()?;
+    // The handler must never block; the reading thread does.
+    sys::set_blocking(read.as_raw_fd())?;
+
+    // The thread exits when the write end is closed on error below.
+    thread::Builder::new()
+        .name("mio-compat-sigchld".to_owned())
+        .spawn(move || {
+            let mut buf = [0u8; 64];
+            loop {
+                match (&read).read(&mut buf) {
+                    Ok(0) => return,
+                    Ok(_) => check_watched(),
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => return,
+                }
+            }
+        })?;
+    signal::subscribe(libc::SIGCHLD, Slot::Wake, write.as_raw_fd())?;
+    // Kept open for the handler, which stays installed.
+    mem::forget(write);
+    Ok(())
+}
+
+fn sigchld_ignored() -> bool {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        libc::sigaction(libc::SIGCHLD, ptr::null(), &mut action) == 0
+            && action.sa_sigaction == libc::SIG_IGN
+    }
+}
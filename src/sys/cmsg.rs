@@ -0,0 +1,135 @@
+//! Helpers to build and parse the ancillary data of `sendmsg`/`recvmsg`.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use libc::{c_int, c_uint};
+
+// Aligned for `cmsghdr`.
+#[repr(C, align(8))]
+pub struct ControlBuf([u8; 256]);
+
+impl ControlBuf {
+    pub fn new() -> ControlBuf {
+        ControlBuf([0; 256])
+    }
+}
+
+impl AsRef<[u8]> for ControlBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for ControlBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+pub struct Encoder<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Encoder<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Encoder<'a> {
+        Encoder { buf, len: 0 }
+    }
+
+    pub fn push<T: Copy>(&mut self, level: c_int, ty: c_int, value: T) {
+        self.push_slice(level, ty, &[value]);
+    }
+
+    pub fn push_slice<T: Copy>(&mut self, level: c_int, ty: c_int, values: &[T]) {
+        let size = mem::size_of_val(values) as c_uint;
+        let space = unsafe { libc::CMSG_SPACE(size) } as usize;
+        assert!(
+            self.len + space <= self.buf.len(),
+            "control buffer too small"
+        );
+        unsafe {
+            let data = self.buf[self.len..].as_mut_ptr();
+            ptr::write_bytes(data, 0, space);
+            let cmsg = data as *mut libc::cmsghdr;
+            (*cmsg).cmsg_level = level;
+            (*cmsg).cmsg_type = ty;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(size) as _;
+            let dst = libc::CMSG_DATA(cmsg) as *mut T;
+            for (i, value) in values.iter().enumerate() {
+                ptr::write_unaligned(dst.add(i), *value);
+            }
+        }
+        self.len += space;
+    }
+
+    pub fn fits(&self, size: usize) -> bool {
+        let space = unsafe { libc::CMSG_SPACE(size as c_uint) } as usize;
+        self.len + space <= self.buf.len()
+    }
+
+    pub fn finish(self) -> &'a [u8] {
+        &self.buf[..self.len]
+    }
+}
+
+pub struct Cmsg<'a> {
+    pub level: c_int,
+    pub ty: c_int,
+    pub data: &'a [u8],
+}
+
+impl<'a> Cmsg<'a> {
+    pub fn read<T: Copy>(&self) -> Option<T> {
+        if self.data.len() < mem::size_of::<T>() {
+            return None;
+        }
+        Some(unsafe { ptr::read_unaligned(self.data.as_ptr() as *const T) })
+    }
+}
+
+pub struct Iter<'a> {
+    msg: libc::msghdr,
+    cmsg: *const libc::cmsghdr,
+    _marker: PhantomData<&'a [u8]>,
+}
+
+pub fn iter(control: &[u8]) -> Iter<'_> {
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_control = control.as_ptr() as *mut _;
+    msg.msg_controllen = control.len() as _;
+    let cmsg = if control.is_empty() {
+        ptr::null()
+    } else {
+        unsafe { libc::CMSG_FIRSTHDR(&msg) as *const _ }
+    };
+    Iter {
+        msg,
+        cmsg,
+        _marker: PhantomData,
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Cmsg<'a>;
+
+    fn next(&mut self) -> Option<Cmsg<'a>> {
+        if self.cmsg.is_null() {
+            return None;
+        }
+        unsafe {
+            let cmsg = &*self.cmsg;
+            let cmsg_len: usize = cmsg.cmsg_len as _;
+            let len = cmsg_len.saturating_sub(libc::CMSG_LEN(0) as usize);
+            let data = slice::from_raw_parts(libc::CMSG_DATA(cmsg), len);
+            self.cmsg = libc::CMSG_NXTHDR(&self.msg, cmsg);
+            Some(Cmsg {
+                level: cmsg.cmsg_level,
+                ty: cmsg.cmsg_type,
+                data,
+            })
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::io::RawFd;
+#[cfg(target_os = "linux")]
+use std::ptr;
+#[cfg(target_os = "linux")]
+use std::slice;
+
+use libc::{c_int, c_void, socklen_t};
+
+pub mod cmsg;
+pub mod signal;
+
+pub trait IsMinusOne {
+    fn is_minus_one(&self) -> bool;
+}
+
+impl IsMinusOne for i32 {
+    fn is_minus_one(&self) -> bool {
+        *self == -1
+    }
+}
+
+impl IsMinusOne for isize {
+    fn is_minus_one(&self) -> bool {
+        *self == -1
+    }
+}
+
+pub fn cvt<T: IsMinusOne>(t: T) -> io::Result<T> {
+    if t.is_minus_one() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(t)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn errno_location() -> *mut c_int {
+    libc::__errno_location()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub unsafe fn errno_location() -> *mut c_int {
+    libc::__error()
+}
+
+/// Creates a non-blocking, close-on-exec socket of the family of `addr`.
+pub fn socket(addr: &SocketAddr, ty: c_int) -> io::Result<RawFd> {
+    let domain = match *addr {
+        SocketAddr::V4(..) => libc::AF_INET,
+        SocketAddr::V6(..) => libc::AF_INET6,
+    };
+    socket_raw(domain, ty)
+}
+
+pub fn socket_raw(domain: c_int, ty: c_int) -> io::Result<RawFd> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        cvt(unsafe { libc::socket(domain, ty | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, 0) })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let fd = cvt(unsafe { libc::socket(domain, ty, 0) })?;
+        let res = unsafe {
+            cvt(libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK))
+                .and_then(|_| cvt(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+        };
+        if let Err(e) = res {
+            unsafe { libc::close(fd) };
+            return Err(e);
+        }
+        Ok(fd)
+    }
+}
+
+pub fn socketpair(domain: c_int, ty: c_int) -> io::Result<(RawFd, RawFd)> {
+    let mut fds = [0; 2];
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let ty = ty | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+        cvt(unsafe { libc::socketpair(domain, ty, 0, fds.as_mut_ptr()) })?;
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        cvt(unsafe { libc::socketpair(domain, ty, 0, fds.as_mut_ptr()) })?;
+        for &fd in &fds {
+            let res = unsafe {
+                cvt(libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK))
+                    .and_then(|_| cvt(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+            };
+            if let Err(e) = res {
+                unsafe {
+                    libc::close(fds[0]);
+                    libc::close(fds[1]);
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok((fds[0], fds[1]))
+}
+
+pub fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    let flags = cvt(unsafe { libc::fcntl(fd, libc::F_GETFL) })?;
+    if flags & libc::O_NONBLOCK == 0 {
+        cvt(unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) })?;
+    }
+    Ok(())
+}
+
+pub fn set_blocking(fd: RawFd) -> io::Result<()> {
+    let flags = cvt(unsafe { libc::fcntl(fd, libc::F_GETFL) })?;
+    if flags & libc::O_NONBLOCK != 0 {
+        cvt(unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) })?;
+    }
+    Ok(())
+}
+
+pub fn setsockopt<T>(fd: RawFd, level: c_int, name: c_int, val: T) -> io::Result<()> {
+    cvt(unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            &val as *const T as *const c_void,
+            mem::size_of::<T>() as socklen_t,
+        )
+    })
+    .map(|_| ())
+}
+
+pub fn getsockopt<T: Copy>(fd: RawFd, level: c_int, name: c_int) -> io::Result<T> {
+    unsafe {
+        let mut val: T = mem::zeroed();
+        let mut len = mem::size_of::<T>() as socklen_t;
+        cvt(libc::getsockopt(
+            fd,
+            level,
+            name,
+            &mut val as *mut T as *mut c_void,
+            &mut len,
+        ))?;
+        Ok(val)
+    }
+}
+
+/// Converts a `SocketAddr` into the raw representation expected by the
+/// socket system calls.
+pub fn socket_addr(addr: &SocketAddr) -> (libc::sockaddr_storage, socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let len = match *addr {
+        SocketAddr::V4(ref a) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = a.port().to_be();
+            sin.sin_addr.s_addr = u32::from_ne_bytes(a.ip().octets());
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(ref a) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = a.port().to_be();
+            sin6.sin6_flowinfo = a.flowinfo();
+            sin6.sin6_addr.s6_addr = a.ip().octets();
+            sin6.sin6_scope_id = a.scope_id();
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    (storage, len as socklen_t)
+}
+
+pub fn to_socket_addr(storage: &libc::sockaddr_storage, len: socklen_t) -> io::Result<SocketAddr> {
+    match c_int::from(storage.ss_family) {
+        libc::AF_INET if len as usize >= mem::size_of::<libc::sockaddr_in>() => {
+            let sin = unsafe { &*(storage as *const _ as *const libc::sockaddr_in) };
+            let ip = Ipv4Addr::from(sin.sin_addr.s_addr.to_ne_bytes());
+            Ok(SocketAddr::V4(SocketAddrV4::new(
+                ip,
+                u16::from_be(sin.sin_port),
+            )))
+        }
+        libc::AF_INET6 if len as usize >= mem::size_of::<libc::sockaddr_in6>() => {
+            let sin6 = unsafe { &*(storage as *const _ as *const libc::sockaddr_in6) };
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(sin6.sin6_addr.s6_addr),
+                u16::from_be(sin6.sin6_port),
+                sin6.sin6_flowinfo,
+                sin6.sin6_scope_id,
+            )))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid socket address",
+        )),
+    }
+}
+
+pub fn send(fd: RawFd, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    cvt(unsafe { libc::send(fd, buf.as_ptr() as *const c_void, buf.len(), flags) })
+        .map(|n| n as usize)
+}
+
+pub fn recv(fd: RawFd, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+    cvt(unsafe { libc::recv(fd, buf.as_mut_ptr() as *mut c_void, buf.len(), flags) })
+        .map(|n| n as usize)
+}
+
+pub struct RecvMsg {
+    pub len: usize,
+    pub flags: c_int,
+    pub addr: libc::sockaddr_storage,
+    pub addr_len: socklen_t,
+    pub control_len: usize,
+}
+
+impl RecvMsg {
+    pub fn socket_addr(&self) -> io::Result<SocketAddr> {
+        to_socket_addr(&self.addr, self.addr_len)
+    }
+}
+
+pub fn recvmsg(
+    fd: RawFd,
+    iov: &mut [libc::iovec],
+    control: &mut [u8],
+    flags: c_int,
+) -> io::Result<RecvMsg> {
+    unsafe {
+        let mut addr: libc::sockaddr_storage = mem::zeroed();
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_name = &mut addr as *mut _ as *mut c_void;
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as socklen_t;
+        msg.msg_iov = iov.as_mut_ptr();
+        msg.msg_iovlen = iov.len() as _;
+        if !control.is_empty() {
+            msg.msg_control = control.as_mut_ptr() as *mut c_void;
+            msg.msg_controllen = control.len() as _;
+        }
+
+        let len = cvt(libc::recvmsg(fd, &mut msg, flags))?;
+        Ok(RecvMsg {
+            len: len as usize,
+            flags: msg.msg_flags,
+            addr,
+            addr_len: msg.msg_namelen,
+            control_len: msg.msg_controllen as usize,
+        })
+    }
+}
+
+pub fn sendmsg(
+    fd: RawFd,
+    iov: &[libc::iovec],
+    addr: Option<&SocketAddr>,
+    control: &[u8],
+    flags: c_int,
+) -> io::Result<usize> {
+    unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        let raw_addr;
+        if let Some(addr) = addr {
+            raw_addr = socket_addr(addr);
+            msg.msg_name = &raw_addr.0 as *const _ as *mut c_void;
+            msg.msg_namelen = raw_addr.1;
+        }
+        msg.msg_iov = iov.as_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = iov.len() as _;
+        if !control.is_empty() {
+            msg.msg_control = control.as_ptr() as *mut c_void;
+            msg.msg_controllen = control.len() as _;
+        }
+
+        cvt(libc::sendmsg(fd, &msg, flags)).map(|n| n as usize)
+    }
+}
+
+// Datagrams handled by one `recvmmsg`/`sendmmsg` call. The scratch space
+// lives on the stack, so batches never allocate.
+#[cfg(target_os = "linux")]
+const MMSG_BATCH: usize = 32;
+
+// Errors after the first datagram was passed to `f` are dropped in favor
+// of the count.
+#[cfg(target_os = "linux")]
+pub fn recvmmsg<F>(fd: RawFd, bufs: &mut [&mut [u8]], flags: c_int, mut f: F) -> io::Result<usize>
+where
+    F: FnMut(RecvMsg, &[u8]) -> io::Result<()>,
+{
+    let mut total = 0;
+    for chunk in bufs.chunks_mut(MMSG_BATCH) {
+        let res = recvmmsg_chunk(fd, chunk, flags, |msg, control| {
+            f(msg, control)?;
+            total += 1;
+            Ok(())
+        });
+        match res {
+            Ok(n) if n == chunk.len() => {}
+            Ok(_) => break,
+            Err(e) if total == 0 => return Err(e),
+            Err(_) => break,
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(target_os = "linux")]
+fn recvmmsg_chunk<F>(fd: RawFd, bufs: &mut [&mut [u8]], flags: c_int, mut f: F) -> io::Result<usize>
+where
+    F: FnMut(RecvMsg, &[u8]) -> io::Result<()>,
+{
+    let mut iovs: [libc::iovec; MMSG_BATCH] = unsafe { mem::zeroed() };
+    let mut addrs: [libc::sockaddr_storage; MMSG_BATCH] = unsafe { mem::zeroed() };
+    // Only what the kernel reports as written is read back, so the control
+    // buffers are not initialized.
+    let mut controls: [mem::MaybeUninit<cmsg::ControlBuf>; MMSG_BATCH] =
+        unsafe { mem::MaybeUninit::uninit().assume_init() };
+    let mut msgs: [libc::mmsghdr; MMSG_BATCH] = unsafe { mem::zeroed() };
+    for (i, buf) in bufs.iter_mut().enumerate() {
+        iovs[i] = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len(),
+        };
+        let hdr = &mut msgs[i].msg_hdr;
+        hdr.msg_name = &mut addrs[i] as *mut _ as *mut c_void;
+        hdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as socklen_t;
+        hdr.msg_iov = &mut iovs[i];
+        hdr.msg_iovlen = 1;
+        hdr.msg_control = controls[i].as_mut_ptr() as *mut c_void;
+        hdr.msg_controllen = mem::size_of::<cmsg::ControlBuf>() as _;
+    }
+
+    let n = cvt(unsafe {
+        libc::recvmmsg(
+            fd,
+            msgs.as_mut_ptr(),
+            bufs.len() as libc::c_uint,
+            flags as _,
+            ptr::null_mut(),
+        )
+    })? as usize;
+
+    for i in 0..n {
+        let hdr = &msgs[i].msg_hdr;
+        let control_len: usize = hdr.msg_controllen as _;
+        let control =
+            unsafe { slice::from_raw_parts(controls[i].as_ptr() as *const u8, control_len) };
+        let msg = RecvMsg {
+            len: msgs[i].msg_len as usize,
+            flags: hdr.msg_flags,
+            addr: addrs[i],
+            addr_len: hdr.msg_namelen,
+            control_len,
+        };
+        f(msg, control)?;
+    }
+    Ok(n)
+}
+
+#[cfg(target_os = "linux")]
+pub fn sendmmsg(
+    fd: RawFd,
+    bufs: &[&[u8]],
+    addrs: Option<&[SocketAddr]>,
+    flags: c_int,
+) -> io::Result<usize> {
+    let mut total = 0;
+    for (i, chunk) in bufs.chunks(MMSG_BATCH).enumerate() {
+        let addrs = addrs.map(|addrs| &addrs[i * MMSG_BATCH..][..chunk.len()]);
+        match sendmmsg_chunk(fd, chunk, addrs, flags) {
+            Ok(n) => {
+                total += n;
+                if n < chunk.len() {
+                    break;
+                }
+            }
+            Err(e) if total == 0 => return Err(e),
+            Err(_) => break,
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(target_os = "linux")]
+fn sendmmsg_chunk(
+    fd: RawFd,
+    bufs: &[&[u8]],
+    addrs: Option<&[SocketAddr]>,
+    flags: c_int,
+) -> io::Result<usize> {
+    let mut iovs: [libc::iovec; MMSG_BATCH] = unsafe { mem::zeroed() };
+    let mut raw_addrs: [(libc::sockaddr_storage, socklen_t); MMSG_BATCH] = unsafe { mem::zeroed() };
+    let mut msgs: [libc::mmsghdr; MMSG_BATCH] = unsafe { mem::zeroed() };
+    for (i, buf) in bufs.iter().enumerate() {
+        iovs[i] = libc::iovec {
+            iov_base: buf.as_ptr() as *mut c_void,
+            iov_len: buf.len(),
+        };
+        let hdr = &mut msgs[i].msg_hdr;
+        if let Some(addrs) = addrs {
+            raw_addrs[i] = socket_addr(&addrs[i]);
+            hdr.msg_name = &raw_addrs[i].0 as *const _ as *mut c_void;
+            hdr.msg_namelen = raw_addrs[i].1;
+        }
+        hdr.msg_iov = &mut iovs[i];
+        hdr.msg_iovlen = 1;
+    }
+
+    cvt(unsafe {
+        libc::sendmmsg(
+            fd,
+            msgs.as_mut_ptr(),
+            bufs.len() as libc::c_uint,
+            flags as _,
+        )
+    })
+    .map(|n| n as usize)
+}
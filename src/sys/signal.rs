@@ -0,0 +1,151 @@
+//! The process-wide signal handler shared by `unix::Signals` and the
+//! `SIGCHLD` fallback of `process::ChildExit`.
+//!
+//! A signal gets the handler installed while either of them needs it. The
+//! handler forwards each signal to the self-pipes of both, then calls the
+//! handler that was installed before it. The disposition found at install
+//! time is restored once neither needs the signal anymore.
+
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use libc::c_int;
+
+use crate::sys;
+
+// Larger than any signal number, real-time signals included.
+pub const MAX_SIGNAL: usize = 65;
+
+#[derive(Debug, Copy, Clone)]
+pub enum Slot {
+    // The whole `siginfo_t` of each signal.
+    Record,
+    // A single byte for each signal.
+    Wake,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_PIPE: AtomicI32 = AtomicI32::new(-1);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_HANDLER: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_FLAGS: AtomicUsize = AtomicUsize::new(0);
+
+static RECORD: [AtomicI32; MAX_SIGNAL] = [NO_PIPE; MAX_SIGNAL];
+static WAKE: [AtomicI32; MAX_SIGNAL] = [NO_PIPE; MAX_SIGNAL];
+static OLD_HANDLER: [AtomicUsize; MAX_SIGNAL] = [NO_HANDLER; MAX_SIGNAL];
+static OLD_FLAGS: [AtomicUsize; MAX_SIGNAL] = [NO_FLAGS; MAX_SIGNAL];
+// Handlers between loading a pipe and writing to it.
+static WRITING: AtomicUsize = AtomicUsize::new(0);
+
+// The disposition to restore for each signal with the handler installed.
+static INSTALLED: Mutex<[Option<libc::sigaction>; MAX_SIGNAL]> = Mutex::new([None; MAX_SIGNAL]);
+
+fn slot(signal: c_int, slot: Slot) -> &'static AtomicI32 {
+    match slot {
+        Slot::Record => &RECORD[signal as usize],
+        Slot::Wake => &WAKE[signal as usize],
+    }
+}
+
+pub fn subscribe(signal: c_int, to: Slot, fd: RawFd) -> io::Result<()> {
+    let mut installed = INSTALLED.lock().unwrap();
+    let pipe = slot(signal, to);
+    if pipe.load(Ordering::SeqCst) >= 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "signal is already handled by another `Signals`",
+        ));
+    }
+    pipe.store(fd, Ordering::SeqCst);
+    if installed[signal as usize].is_some() {
+        return Ok(());
+    }
+
+    unsafe {
+        let mut old: libc::sigaction = mem::zeroed();
+        let res = sys::cvt(libc::sigaction(signal, ptr::null(), &mut old)).and_then(|_| {
+            // Set before the handler can run and chain to it.
+            OLD_HANDLER[signal as usize].store(old.sa_sigaction, Ordering::SeqCst);
+            OLD_FLAGS[signal as usize].store(old.sa_flags as usize, Ordering::SeqCst);
+
+            let mut action: libc::sigaction = mem::zeroed();
+            let handler: extern "C" fn(c_int, *mut libc::siginfo_t, *mut libc::c_void) = handler;
+            action.sa_sigaction = handler as libc::sighandler_t;
+            action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            sys::cvt(libc::sigaction(signal, &action, ptr::null_mut()))
+        });
+        if let Err(e) = res {
+            pipe.store(-1, Ordering::SeqCst);
+            return Err(e);
+        }
+        installed[signal as usize] = Some(old);
+    }
+    Ok(())
+}
+
+// The pipe may be closed once this returns.
+pub fn unsubscribe(signal: c_int, from: Slot) {
+    let mut installed = INSTALLED.lock().unwrap();
+    slot(signal, from).store(-1, Ordering::SeqCst);
+    // A handler on another thread may have loaded the pipe just before.
+    while WRITING.load(Ordering::SeqCst) != 0 {
+        thread::yield_now();
+    }
+    if RECORD[signal as usize].load(Ordering::SeqCst) >= 0
+        || WAKE[signal as usize].load(Ordering::SeqCst) >= 0
+    {
+        return;
+    }
+    if let Some(old) = installed[signal as usize].take() {
+        unsafe { libc::sigaction(signal, &old, ptr::null_mut()) };
+        OLD_HANDLER[signal as usize].store(libc::SIG_DFL, Ordering::SeqCst);
+        OLD_FLAGS[signal as usize].store(0, Ordering::SeqCst);
+    }
+}
+
+extern "C" fn handler(signal: c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+    let slot = signal as usize;
+    if slot >= MAX_SIGNAL {
+        return;
+    }
+    unsafe {
+        let errno = *sys::errno_location();
+        WRITING.fetch_add(1, Ordering::SeqCst);
+        // A full pipe drops the signal, as the kernel coalesces them too.
+        let fd = RECORD[slot].load(Ordering::SeqCst);
+        if fd >= 0 && !info.is_null() {
+            libc::write(
+                fd,
+                info as *const libc::c_void,
+                mem::size_of::<libc::siginfo_t>(),
+            );
+        }
+        let fd = WAKE[slot].load(Ordering::SeqCst);
+        if fd >= 0 {
+            let byte = 1u8;
+            libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+        WRITING.fetch_sub(1, Ordering::SeqCst);
+        *sys::errno_location() = errno;
+
+        let old = OLD_HANDLER[slot].load(Ordering::Relaxed);
+        if old == libc::SIG_DFL || old == libc::SIG_IGN {
+            return;
+        }
+        if OLD_FLAGS[slot].load(Ordering::Relaxed) as c_int & libc::SA_SIGINFO != 0 {
+            let old: extern "C" fn(c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                mem::transmute(old);
+            old(signal, info, context);
+        } else {
+            let old: extern "C" fn(c_int) = mem::transmute(old);
+            old(signal);
+        }
+    }
+}
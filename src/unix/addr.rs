@@ -0,0 +1,221 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+use libc::{c_int, sockaddr, sockaddr_un, socklen_t};
+
+use crate::sys;
+
+/// The address of a Unix domain socket: a filesystem path, an abstract name
+/// (Linux only) or unnamed.
+#[derive(Clone)]
+pub struct SocketAddr {
+    addr: sockaddr_un,
+    len: socklen_t,
+}
+
+enum AddressKind<'a> {
+    Unnamed,
+    Pathname(&'a Path),
+    Abstract(&'a [u8]),
+}
+
+fn sun_path_offset(addr: &sockaddr_un) -> usize {
+    addr.sun_path.as_ptr() as usize - addr as *const _ as usize
+}
+
+impl SocketAddr {
+    /// Creates an address referring to the socket bound at `path`.
+    pub fn from_pathname<P: AsRef<Path>>(path: P) -> io::Result<SocketAddr> {
+        let bytes = path.as_ref().as_os_str().as_bytes();
+        if bytes.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path must not contain interior nul bytes",
+            ));
+        }
+        // Leave room for the terminating nul.
+        let mut addr = SocketAddr::empty();
+        if bytes.len() >= addr.addr.sun_path.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path must be shorter than SUN_LEN",
+            ));
+        }
+        for (dst, src) in addr.addr.sun_path.iter_mut().zip(bytes) {
+            *dst = *src as libc::c_char;
+        }
+        addr.len = (sun_path_offset(&addr.addr) + bytes.len() + 1) as socklen_t;
+        Ok(addr)
+    }
+
+    /// Creates an address in the abstract namespace, which is not backed by
+    /// the filesystem and disappears with the last socket bound to it.
+    #[cfg(target_os = "linux")]
+    pub fn from_abstract_name<N: AsRef<[u8]>>(name: N) -> io::Result<SocketAddr> {
+        let name = name.as_ref();
+        let mut addr = SocketAddr::empty();
+        if name.len() + 1 > addr.addr.sun_path.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "abstract name must be shorter than SUN_LEN",
+            ));
+        }
+        // The leading nul byte marks the name as abstract.
+        addr.addr.sun_path[0] = 0;
+        for (dst, src) in addr.addr.sun_path[1..].iter_mut().zip(name) {
+            *dst = *src as libc::c_char;
+        }
+        addr.len = (sun_path_offset(&addr.addr) + 1 + name.len()) as socklen_t;
+        Ok(addr)
+    }
+
+    fn empty() -> SocketAddr {
+        let mut addr: sockaddr_un = unsafe { mem::zeroed() };
+        addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        SocketAddr {
+            len: sun_path_offset(&addr) as socklen_t,
+            addr,
+        }
+    }
+
+    pub(crate) fn new<F>(f: F) -> io::Result<SocketAddr>
+    where
+        F: FnOnce(*mut sockaddr, *mut socklen_t) -> c_int,
+    {
+        let mut addr = SocketAddr::empty();
+        addr.len = mem::size_of::<sockaddr_un>() as socklen_t;
+        sys::cvt(f(&mut addr.addr as *mut _ as *mut sockaddr, &mut addr.len))?;
+        // Some platforms report unnamed addresses with a zero length.
+        if addr.len == 0 {
+            addr.len = sun_path_offset(&addr.addr) as socklen_t;
+        } else if c_int::from(addr.addr.sun_family) != libc::AF_UNIX {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "file descriptor did not correspond to a Unix socket",
+            ));
+        }
+        Ok(addr)
+    }
+
+    pub(crate) fn as_raw(&self) -> (*const sockaddr, socklen_t) {
+        (&self.addr as *const _ as *const sockaddr, self.len)
+    }
+
+    pub fn is_unnamed(&self) -> bool {
+        matches!(self.kind(), AddressKind::Unnamed)
+    }
+
+    /// Returns the path of a pathname address.
+    pub fn as_pathname(&self) -> Option<&Path> {
+        match self.kind() {
+            AddressKind::Pathname(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of an abstract address, without the leading nul.
+    #[cfg(target_os = "linux")]
+    pub fn as_abstract_name(&self) -> Option<&[u8]> {
+        match self.kind() {
+            AddressKind::Abstract(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> AddressKind<'_> {
+        let path = unsafe { &*(&self.addr.sun_path as *const [libc::c_char] as *const [u8]) };
+        // A length short of the path, or past it, may come from the kernel.
+        let len = (self.len as usize)
+            .saturating_sub(sun_path_offset(&self.addr))
+            .min(path.len());
+
+        // A lone nul is the terminator of an empty pathname, not an empty
+        // abstract name.
+        if len == 0 {
+            AddressKind::Unnamed
+        } else if cfg!(target_os = "linux") && len > 1 && path[0] == 0 {
+            AddressKind::Abstract(&path[1..len])
+        } else {
+            // The terminating nul may or may not be counted in the length.
+            let path = &path[..len];
+            let end = path.iter().position(|&b| b == 0).unwrap_or(len);
+            AddressKind::Pathname(OsStr::from_bytes(&path[..end]).as_ref())
+        }
+    }
+}
+
+pub(crate) fn local_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    SocketAddr::new(|addr, len| unsafe { libc::getsockname(fd, addr, len) })
+}
+
+pub(crate) fn peer_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    SocketAddr::new(|addr, len| unsafe { libc::getpeername(fd, addr, len) })
+}
+
+pub(crate) fn bind(fd: RawFd, addr: &SocketAddr) -> io::Result<()> {
+    let (addr, len) = addr.as_raw();
+    sys::cvt(unsafe { libc::bind(fd, addr, len) }).map(|_| ())
+}
+
+pub(crate) fn connect(fd: RawFd, addr: &SocketAddr) -> io::Result<()> {
+    let (addr, len) = addr.as_raw();
+    match sys::cvt(unsafe { libc::connect(fd, addr, len) }) {
+        Ok(_) => Ok(()),
+        Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+pub(crate) fn accept(fd: RawFd) -> io::Result<(RawFd, SocketAddr)> {
+    let mut conn = -1;
+    #[cfg(target_os = "linux")]
+    let addr = SocketAddr::new(|addr, len| unsafe {
+        conn = libc::accept4(fd, addr, len, libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC);
+        conn
+    })?;
+    #[cfg(not(target_os = "linux"))]
+    let addr = SocketAddr::new(|addr, len| unsafe {
+        conn = libc::accept(fd, addr, len);
+        conn
+    })?;
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let res = unsafe {
+            sys::cvt(libc::fcntl(conn, libc::F_SETFD, libc::FD_CLOEXEC))
+                .and_then(|_| sys::cvt(libc::fcntl(conn, libc::F_SETFL, libc::O_NONBLOCK)))
+        };
+        if let Err(e) = res {
+            unsafe { libc::close(conn) };
+            return Err(e);
+        }
+    }
+    Ok((conn, addr))
+}
+
+impl fmt::Debug for SocketAddr {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind() {
+            AddressKind::Unnamed => write!(fmt, "(unnamed)"),
+            AddressKind::Abstract(name) => write!(fmt, "{:?} (abstract)", AsciiEscaped(name)),
+            AddressKind::Pathname(path) => write!(fmt, "{:?} (pathname)", path),
+        }
+    }
+}
+
+struct AsciiEscaped<'a>(&'a [u8]);
+
+impl fmt::Debug for AsciiEscaped<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "\"")?;
+        for byte in self.0.iter().cloned().flat_map(std::ascii::escape_default) {
+            write!(fmt, "{}", byte as char)?;
+        }
+        write!(fmt, "\"")
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::os::unix::net;
+use std::path::Path;
+
+use crate::sys;
+use crate::unix::{addr, msg, EventedFd, SocketAddr, UnixRecvMeta};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+pub struct UnixDatagram(net::UnixDatagram);
+
+impl UnixDatagram {
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagram> {
+        UnixDatagram::bind_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    /// Binds to `addr`, which may be an abstract address.
+    pub fn bind_addr(addr: &SocketAddr) -> io::Result<UnixDatagram> {
+        let socket = UnixDatagram::unbound()?;
+        addr::bind(socket.as_raw_fd(), addr)?;
+        Ok(socket)
+    }
+
+    /// Creates a socket that is not bound to any address.
+    pub fn unbound() -> io::Result<UnixDatagram> {
+        let fd = sys::socket_raw(libc::AF_UNIX, libc::SOCK_DGRAM)?;
+        Ok(unsafe { UnixDatagram::from_raw_fd(fd) })
+    }
+
+    /// Creates a pair of connected sockets.
+    pub fn pair() -> io::Result<(UnixDatagram, UnixDatagram)> {
+        let (a, b) = net::UnixDatagram::pair()?;
+        Ok((UnixDatagram::from_std(a)?, UnixDatagram::from_std(b)?))
+    }
+
+    pub fn from_std(socket: net::UnixDatagram) -> io::Result<UnixDatagram> {
+        socket.set_nonblocking(true)?;
+        Ok(UnixDatagram(socket))
+    }
+
+    pub fn connect<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.connect_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    pub fn connect_addr(&self, addr: &SocketAddr) -> io::Result<()> {
+        addr::connect(self.as_raw_fd(), addr)
+    }
+
+    pub fn try_clone(&self) -> io::Result<UnixDatagram> {
+        Ok(UnixDatagram(self.0.try_clone()?))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    pub fn send_to<P: AsRef<Path>>(&self, buf: &[u8], path: P) -> io::Result<usize> {
+        self.send_to_addr(buf, &SocketAddr::from_pathname(path)?)
+    }
+
+    pub fn send_to_addr(&self, buf: &[u8], target: &SocketAddr) -> io::Result<usize> {
+        let (addr, len) = target.as_raw();
+        let n = sys::cvt(unsafe {
+            libc::sendto(
+                self.as_raw_fd(),
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+                0,
+                addr,
+                len,
+            )
+        })?;
+        Ok(n as usize)
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let mut n = 0;
+        let addr = SocketAddr::new(|addr, len| unsafe {
+            n = libc::recvfrom(
+                self.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                0,
+                addr,
+                len,
+            );
+            if n < 0 {
+                -1
+            } else {
+                0
+            }
+        })?;
+        Ok((n as usize, addr))
+    }
+
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.0.send(buf)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.recv(buf)
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.0.shutdown(how)
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.0.take_error()
+    }
+
+    /// Sends `buf` together with the file descriptors `fds` (`SCM_RIGHTS`).
+    /// The descriptors stay open in this process.
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+        msg::send_with_fds(self.as_raw_fd(), buf, fds)
+    }
+
+    /// Receives into `buf`, appending the file descriptors passed along with
+    /// the data to `fds`. They are received with close-on-exec set.
+    pub fn recv_with_fds(
+        &self,
+        buf: &mut [u8],
+        fds: &mut Vec<OwnedFd>,
+    ) -> io::Result<UnixRecvMeta> {
+        msg::recv(self.as_raw_fd(), buf, Some(fds))
+    }
+
+    /// Makes the kernel attach the credentials of the sender to every
+    /// received message (`SO_PASSCRED`), see `recv_with_cred`.
+    #[cfg(target_os = "linux")]
+    pub fn set_passcred(&self, on: bool) -> io::Result<()> {
+        sys::setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PASSCRED,
+            on as libc::c_int,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn passcred(&self) -> io::Result<bool> {
+        sys::getsockopt::<libc::c_int>(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PASSCRED)
+            .map(|on| on != 0)
+    }
+
+    /// Receives a message into `buf` along with the credentials of its
+    /// sender, reported by `UnixRecvMeta::cred`. Any descriptors passed along
+    /// with the message are closed.
+    #[cfg(target_os = "linux")]
+    pub fn recv_with_cred(&self, buf: &mut [u8]) -> io::Result<UnixRecvMeta> {
+        msg::recv(self.as_raw_fd(), buf, None)
+    }
+}
+
+impl Evented for UnixDatagram {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for UnixDatagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl IntoRawFd for UnixDatagram {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixDatagram {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixDatagram {
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixDatagram {
+        UnixDatagram(net::UnixDatagram::from_raw_fd(fd))
+    }
+}
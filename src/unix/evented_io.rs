@@ -0,0 +1,120 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+
+use crate::sys;
+use crate::unix::EventedFd;
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+/// Owns an I/O object backed by a file descriptor and makes it `Evented`.
+///
+/// The descriptor is put in non-blocking mode, so reads and writes return
+/// `WouldBlock` instead of blocking, and it is closed when the wrapper is
+/// dropped along with the inner value.
+pub struct EventedIo<T: AsRawFd + IntoRawFd> {
+    inner: T,
+}
+
+impl<T: AsRawFd + IntoRawFd> EventedIo<T> {
+    pub fn new(inner: T) -> io::Result<EventedIo<T>> {
+        sys::set_nonblocking(inner.as_raw_fd())?;
+        Ok(EventedIo { inner })
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the inner value, which is left in non-blocking mode.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd + Read> Read for EventedIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<'a, T: AsRawFd + IntoRawFd> Read for &'a EventedIo<T>
+where
+    &'a T: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&self.inner).read(buf)
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd + Write> Write for EventedIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<'a, T: AsRawFd + IntoRawFd> Write for &'a EventedIo<T>
+where
+    &'a T: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&self.inner).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.inner).flush()
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd> Evented for EventedIo<T> {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd + fmt::Debug> fmt::Debug for EventedIo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventedIo")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd> IntoRawFd for EventedIo<T> {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw_fd()
+    }
+}
+
+impl<T: AsRawFd + IntoRawFd> AsRawFd for EventedIo<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
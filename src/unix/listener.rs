@@ -0,0 +1,100 @@
+use std::fmt;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::net;
+use std::path::Path;
+
+use crate::sys;
+use crate::unix::{addr, EventedFd, SocketAddr, UnixStream};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+pub struct UnixListener(net::UnixListener);
+
+impl UnixListener {
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixListener> {
+        UnixListener::bind_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    /// Binds to `addr`, which may be an abstract address.
+    pub fn bind_addr(addr: &SocketAddr) -> io::Result<UnixListener> {
+        let fd = sys::socket_raw(libc::AF_UNIX, libc::SOCK_STREAM)?;
+        let listener = unsafe { UnixListener::from_raw_fd(fd) };
+        addr::bind(fd, addr)?;
+        sys::cvt(unsafe { libc::listen(fd, 128) })?;
+        Ok(listener)
+    }
+
+    pub fn from_std(listener: net::UnixListener) -> io::Result<UnixListener> {
+        listener.set_nonblocking(true)?;
+        Ok(UnixListener(listener))
+    }
+
+    /// Accepts a new connection, returning `WouldBlock` when none is
+    /// pending.
+    pub fn accept(&self) -> io::Result<(UnixStream, SocketAddr)> {
+        let (fd, addr) = addr::accept(self.as_raw_fd())?;
+        Ok((unsafe { UnixStream::from_raw_fd(fd) }, addr))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    pub fn try_clone(&self) -> io::Result<UnixListener> {
+        Ok(UnixListener(self.0.try_clone()?))
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.0.take_error()
+    }
+}
+
+impl Evented for UnixListener {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for UnixListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl IntoRawFd for UnixListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixListener {
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixListener {
+        UnixListener(net::UnixListener::from_raw_fd(fd))
+    }
+}
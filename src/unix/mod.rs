@@ -7,6 +7,30 @@ use crate::{Evented, Poll, PollOpt, Ready, Token};
 use mio::event::Source;
 use mio::unix::SourceFd;
 
+mod addr;
+mod datagram;
+mod evented_io;
+mod listener;
+mod msg;
+mod pipe;
+#[cfg(target_os = "linux")]
+mod seqpacket;
+mod signals;
+mod stream;
+
+pub use self::addr::SocketAddr;
+pub use self::datagram::UnixDatagram;
+pub use self::evented_io::EventedIo;
+pub use self::listener::UnixListener;
+#[cfg(target_os = "linux")]
+pub use self::msg::UCred;
+pub use self::msg::UnixRecvMeta;
+pub use self::pipe::{pipe, PipeReader, PipeWriter};
+#[cfg(target_os = "linux")]
+pub use self::seqpacket::{UnixSeqpacket, UnixSeqpacketListener};
+pub use self::signals::{SignalInfo, Signals};
+pub use self::stream::UnixStream;
+
 #[derive(Debug)]
 pub struct EventedFd<'a>(pub &'a RawFd);
 
@@ -23,7 +47,7 @@ impl<'a> Evented for EventedFd<'a> {
         SourceFd(&self.0).register(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -38,7 +62,7 @@ impl<'a> Evented for EventedFd<'a> {
         SourceFd(&self.0).reregister(
             registry,
             mio::Token(token.0),
-            convert_ready_to_interests(interest).unwrap(),
+            convert_ready_to_interests(interest)?,
         )
     }
 
@@ -0,0 +1,160 @@
+use std::io;
+use std::mem;
+#[cfg(not(target_os = "linux"))]
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::{FromRawFd, OwnedFd, RawFd};
+
+use libc::c_int;
+
+use crate::net::MsgFlags;
+use crate::sys::{self, cmsg};
+
+/// The credentials of a process, as reported by `SO_PEERCRED` or
+/// `SCM_CREDENTIALS`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct UCred {
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+#[cfg(target_os = "linux")]
+impl UCred {
+    pub(crate) fn from_raw(cred: libc::ucred) -> UCred {
+        UCred {
+            pid: cred.pid,
+            uid: cred.uid,
+            gid: cred.gid,
+        }
+    }
+
+    pub fn pid(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+}
+
+/// Information about a message received with `recv_with_fds` or
+/// `recv_with_cred`.
+#[derive(Debug, Clone)]
+pub struct UnixRecvMeta {
+    len: usize,
+    truncated: bool,
+    fds_truncated: bool,
+    #[cfg(target_os = "linux")]
+    cred: Option<UCred>,
+}
+
+impl UnixRecvMeta {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the message did not fit in the buffer and its tail
+    /// was discarded (`MSG_TRUNC`). Only datagram sockets truncate.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns true if more descriptors were sent than could be received
+    /// (`MSG_CTRUNC`). The kernel closes the ones that did not fit.
+    pub fn fds_truncated(&self) -> bool {
+        self.fds_truncated
+    }
+
+    /// Returns the credentials of the sender, if `set_passcred` is enabled
+    /// on the receiving socket.
+    #[cfg(target_os = "linux")]
+    pub fn cred(&self) -> Option<UCred> {
+        self.cred
+    }
+}
+
+pub(crate) fn send_with_fds(socket: RawFd, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+    let mut control = cmsg::ControlBuf::new();
+    let mut encoder = cmsg::Encoder::new(control.as_mut());
+    if !fds.is_empty() {
+        if !encoder.fits(mem::size_of_val(fds)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many file descriptors",
+            ));
+        }
+        encoder.push_slice(libc::SOL_SOCKET, libc::SCM_RIGHTS, fds);
+    }
+    let iov = [libc::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    }];
+    sys::sendmsg(
+        socket,
+        &iov,
+        None,
+        encoder.finish(),
+        MsgFlags::empty().bits(),
+    )
+}
+
+// Descriptors are closed when `fds` is `None`.
+pub(crate) fn recv(
+    socket: RawFd,
+    buf: &mut [u8],
+    fds: Option<&mut Vec<OwnedFd>>,
+) -> io::Result<UnixRecvMeta> {
+    let mut control = cmsg::ControlBuf::new();
+    let mut iov = [libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    }];
+    #[cfg(target_os = "linux")]
+    let flags = MsgFlags::empty().bits() | libc::MSG_CMSG_CLOEXEC;
+    #[cfg(not(target_os = "linux"))]
+    let flags = MsgFlags::empty().bits();
+    let msg = sys::recvmsg(socket, &mut iov, control.as_mut(), flags)?;
+
+    let mut meta = UnixRecvMeta {
+        len: msg.len,
+        truncated: msg.flags & libc::MSG_TRUNC != 0,
+        fds_truncated: msg.flags & libc::MSG_CTRUNC != 0,
+        #[cfg(target_os = "linux")]
+        cred: None,
+    };
+    let mut received = Vec::new();
+    for cmsg in cmsg::iter(&control.as_ref()[..msg.control_len]) {
+        match (cmsg.level, cmsg.ty) {
+            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => {
+                for raw in cmsg.data.chunks_exact(mem::size_of::<c_int>()) {
+                    let fd = c_int::from_ne_bytes([raw[0], raw[1], raw[2], raw[3]]);
+                    received.push(unsafe { OwnedFd::from_raw_fd(fd) });
+                }
+            }
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => {
+                meta.cred = cmsg.read::<libc::ucred>().map(UCred::from_raw);
+            }
+            _ => {}
+        }
+    }
+
+    // Without `MSG_CMSG_CLOEXEC` the flag can only be set after the fact.
+    #[cfg(not(target_os = "linux"))]
+    for fd in &received {
+        sys::cvt(unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) })?;
+    }
+    if let Some(fds) = fds {
+        fds.append(&mut received);
+    }
+    Ok(meta)
+}
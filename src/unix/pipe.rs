@@ -0,0 +1,311 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::process::{ChildStderr, ChildStdin, ChildStdout};
+use std::ptr;
+
+use crate::sys;
+use crate::unix::{EventedFd, UnixReady};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+/// Creates a non-blocking, close-on-exec pipe.
+///
+/// The reader becomes readable with `UnixReady::hup` once every writer is
+/// closed, and writes to a pipe without readers fail with `BrokenPipe`
+/// instead of raising `SIGPIPE`.
+pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
+    let mut fds = [0; 2];
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let flags = libc::O_NONBLOCK | libc::O_CLOEXEC;
+        sys::cvt(unsafe { libc::pipe2(fds.as_mut_ptr(), flags) })?;
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        sys::cvt(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+        for &fd in &fds {
+            let res = unsafe {
+                sys::cvt(libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK))
+                    .and_then(|_| sys::cvt(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+            };
+            if let Err(e) = res {
+                unsafe {
+                    libc::close(fds[0]);
+                    libc::close(fds[1]);
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    unsafe {
+        Ok((
+            PipeReader::from_raw_fd(fds[0]),
+            PipeWriter::from_raw_fd(fds[1]),
+        ))
+    }
+}
+
+/// The reading end of a pipe.
+pub struct PipeReader(OwnedFd);
+
+impl PipeReader {
+    pub fn from_stdout(stdout: ChildStdout) -> io::Result<PipeReader> {
+        sys::set_nonblocking(stdout.as_raw_fd())?;
+        Ok(PipeReader(stdout.into()))
+    }
+
+    pub fn from_stderr(stderr: ChildStderr) -> io::Result<PipeReader> {
+        sys::set_nonblocking(stderr.as_raw_fd())?;
+        Ok(PipeReader(stderr.into()))
+    }
+
+    fn read_fd(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = sys::cvt(unsafe {
+            libc::read(
+                self.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+            )
+        })?;
+        Ok(n as usize)
+    }
+}
+
+/// The writing end of a pipe.
+///
+/// Each write first looks up the disposition of `SIGPIPE`. When it is not
+/// ignored, the write also costs three calls to mask the signal, and one
+/// more to consume it on `EPIPE`.
+pub struct PipeWriter(OwnedFd);
+
+impl PipeWriter {
+    pub fn from_stdin(stdin: ChildStdin) -> io::Result<PipeWriter> {
+        sys::set_nonblocking(stdin.as_raw_fd())?;
+        Ok(PipeWriter(stdin.into()))
+    }
+
+    // Unless `SIGPIPE` is ignored, as the Rust runtime sets it up, the
+    // signal is blocked for the calling thread around the write and a
+    // `SIGPIPE` it raised is consumed, so a closed reader is reported as
+    // `EPIPE` whatever the disposition. The disposition is looked up on
+    // every write, as it may change at any time.
+    fn write_fd(&self, buf: &[u8]) -> io::Result<usize> {
+        if sigpipe_ignored() {
+            let n = sys::cvt(unsafe {
+                libc::write(
+                    self.as_raw_fd(),
+                    buf.as_ptr() as *const libc::c_void,
+                    buf.len(),
+                )
+            })?;
+            return Ok(n as usize);
+        }
+
+        unsafe {
+            let mut pipe: libc::sigset_t = mem::zeroed();
+            libc::sigemptyset(&mut pipe);
+            libc::sigaddset(&mut pipe, libc::SIGPIPE);
+
+            // A `SIGPIPE` that was already pending is not ours to consume.
+            let mut pending: libc::sigset_t = mem::zeroed();
+            libc::sigpending(&mut pending);
+            let was_pending = libc::sigismember(&pending, libc::SIGPIPE) == 1;
+
+            let mut old: libc::sigset_t = mem::zeroed();
+            let err = libc::pthread_sigmask(libc::SIG_BLOCK, &pipe, &mut old);
+            if err != 0 {
+                return Err(io::Error::from_raw_os_error(err));
+            }
+
+            let res = sys::cvt(libc::write(
+                self.as_raw_fd(),
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+            ));
+
+            if !was_pending {
+                if let Err(ref e) = res {
+                    if e.raw_os_error() == Some(libc::EPIPE) {
+                        consume_sigpipe(&pipe);
+                    }
+                }
+            }
+            libc::pthread_sigmask(libc::SIG_SETMASK, &old, ptr::null_mut());
+            res.map(|n| n as usize)
+        }
+    }
+}
+
+fn sigpipe_ignored() -> bool {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        libc::sigaction(libc::SIGPIPE, ptr::null(), &mut action) == 0
+            && action.sa_sigaction == libc::SIG_IGN
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+unsafe fn consume_sigpipe(pipe: &libc::sigset_t) {
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    while libc::sigtimedwait(pipe, ptr::null_mut(), &timeout) == -1
+        && io::Error::last_os_error().raw_os_error() == Some(libc::EINTR)
+    {}
+}
+
+// Without `sigtimedwait`, only wait for the signal if it is known to be
+// pending, as `sigwait` would block otherwise.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe fn consume_sigpipe(pipe: &libc::sigset_t) {
+    let mut pending: libc::sigset_t = mem::zeroed();
+    libc::sigpending(&mut pending);
+    if libc::sigismember(&pending, libc::SIGPIPE) == 1 {
+        let mut sig = 0;
+        libc::sigwait(pipe, &mut sig);
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_fd(buf)
+    }
+}
+
+impl Read for &PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_fd(buf)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_fd(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for &PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_fd(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Evented for PipeReader {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        poll.report_unix_ready(token, UnixReady::hup());
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        poll.report_unix_ready(token, UnixReady::hup());
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl Evented for PipeWriter {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        poll.report_unix_ready(token, UnixReady::hup());
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        poll.report_unix_ready(token, UnixReady::hup());
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for PipeReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeReader")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl fmt::Debug for PipeWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeWriter")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl IntoRawFd for PipeReader {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for PipeReader {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for PipeReader {
+    unsafe fn from_raw_fd(fd: RawFd) -> PipeReader {
+        PipeReader(OwnedFd::from_raw_fd(fd))
+    }
+}
+
+impl IntoRawFd for PipeWriter {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for PipeWriter {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for PipeWriter {
+    unsafe fn from_raw_fd(fd: RawFd) -> PipeWriter {
+        PipeWriter(OwnedFd::from_raw_fd(fd))
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::path::Path;
+
+use crate::net::MsgFlags;
+use crate::sys;
+use crate::unix::{addr, msg, EventedFd, SocketAddr, UnixRecvMeta};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+/// A listening `SOCK_SEQPACKET` Unix socket.
+pub struct UnixSeqpacketListener(OwnedFd);
+
+impl UnixSeqpacketListener {
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixSeqpacketListener> {
+        UnixSeqpacketListener::bind_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    /// Binds to `addr`, which may be an abstract address.
+    pub fn bind_addr(addr: &SocketAddr) -> io::Result<UnixSeqpacketListener> {
+        let fd = sys::socket_raw(libc::AF_UNIX, libc::SOCK_SEQPACKET)?;
+        let listener = unsafe { UnixSeqpacketListener::from_raw_fd(fd) };
+        addr::bind(fd, addr)?;
+        sys::cvt(unsafe { libc::listen(fd, 128) })?;
+        Ok(listener)
+    }
+
+    /// Accepts a new connection, returning `WouldBlock` when none is
+    /// pending.
+    pub fn accept(&self) -> io::Result<(UnixSeqpacket, SocketAddr)> {
+        let (fd, addr) = addr::accept(self.as_raw_fd())?;
+        Ok((unsafe { UnixSeqpacket::from_raw_fd(fd) }, addr))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        take_error(self.as_raw_fd())
+    }
+}
+
+/// A connected `SOCK_SEQPACKET` Unix socket, which preserves message
+/// boundaries like a datagram socket and delivers messages reliably and in
+/// order like a stream socket.
+pub struct UnixSeqpacket(OwnedFd);
+
+impl UnixSeqpacket {
+    /// Connects to the socket bound at `path` without blocking.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<UnixSeqpacket> {
+        UnixSeqpacket::connect_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    /// Connects to the socket at `addr`, which may be an abstract address.
+    pub fn connect_addr(addr: &SocketAddr) -> io::Result<UnixSeqpacket> {
+        let fd = sys::socket_raw(libc::AF_UNIX, libc::SOCK_SEQPACKET)?;
+        let socket = unsafe { UnixSeqpacket::from_raw_fd(fd) };
+        addr::connect(fd, addr)?;
+        Ok(socket)
+    }
+
+    /// Creates a pair of connected sockets.
+    pub fn pair() -> io::Result<(UnixSeqpacket, UnixSeqpacket)> {
+        let (a, b) = sys::socketpair(libc::AF_UNIX, libc::SOCK_SEQPACKET)?;
+        unsafe { Ok((UnixSeqpacket::from_raw_fd(a), UnixSeqpacket::from_raw_fd(b))) }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    /// Sends `buf` as a single message. A closed peer is reported as
+    /// `BrokenPipe` without raising `SIGPIPE`.
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        sys::send(self.as_raw_fd(), buf, MsgFlags::nosignal().bits())
+    }
+
+    /// Receives a single message into `buf`. The part of a message that
+    /// does not fit is discarded; `recv_with_fds` reports when that happens.
+    ///
+    /// `Ok(0)` is returned both for an empty message and once the peer has
+    /// shut down, and `recv_with_fds` cannot tell them apart either. A
+    /// protocol that sends empty messages must mark its end itself.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        sys::recv(self.as_raw_fd(), buf, MsgFlags::empty().bits())
+    }
+
+    /// Sends `buf` as a single message together with the file descriptors
+    /// `fds` (`SCM_RIGHTS`).
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+        msg::send_with_fds(self.as_raw_fd(), buf, fds)
+    }
+
+    /// Receives a single message into `buf`, appending the file descriptors
+    /// passed along with it to `fds`.
+    pub fn recv_with_fds(
+        &self,
+        buf: &mut [u8],
+        fds: &mut Vec<OwnedFd>,
+    ) -> io::Result<UnixRecvMeta> {
+        msg::recv(self.as_raw_fd(), buf, Some(fds))
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        let how = match how {
+            Shutdown::Read => libc::SHUT_RD,
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Both => libc::SHUT_RDWR,
+        };
+        sys::cvt(unsafe { libc::shutdown(self.as_raw_fd(), how) }).map(|_| ())
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        take_error(self.as_raw_fd())
+    }
+}
+
+fn take_error(fd: RawFd) -> io::Result<Option<io::Error>> {
+    let errno: libc::c_int = sys::getsockopt(fd, libc::SOL_SOCKET, libc::SO_ERROR)?;
+    if errno == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(io::Error::from_raw_os_error(errno)))
+    }
+}
+
+impl Evented for UnixSeqpacketListener {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for UnixSeqpacketListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixSeqpacketListener")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl IntoRawFd for UnixSeqpacketListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixSeqpacketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixSeqpacketListener {
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixSeqpacketListener {
+        UnixSeqpacketListener(OwnedFd::from_raw_fd(fd))
+    }
+}
+
+impl Evented for UnixSeqpacket {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for UnixSeqpacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixSeqpacket")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl IntoRawFd for UnixSeqpacket {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixSeqpacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixSeqpacket {
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixSeqpacket {
+        UnixSeqpacket(OwnedFd::from_raw_fd(fd))
+    }
+}
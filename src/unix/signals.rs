@@ -0,0 +1,186 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+
+use libc::c_int;
+
+use crate::sys;
+use crate::sys::signal::{self, Slot, MAX_SIGNAL};
+use crate::unix::EventedFd;
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+/// Delivers a set of signals as readable readiness.
+///
+/// A process-wide handler forwarding each signal to a self-pipe is
+/// installed, so the signals are received whichever thread they are
+/// delivered to. The handler calls any handler installed before it, and the
+/// previous dispositions are restored on drop. Only one `Signals` can
+/// handle a given signal at a time.
+pub struct Signals {
+    fd: OwnedFd,
+    restore: Restore,
+}
+
+struct Restore {
+    write: OwnedFd,
+    signals: Vec<c_int>,
+}
+
+/// A received signal and the `siginfo` fields that describe its origin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SignalInfo {
+    signal: c_int,
+    code: c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    status: c_int,
+}
+
+impl SignalInfo {
+    pub fn signal(&self) -> c_int {
+        self.signal
+    }
+
+    /// Returns `si_code`, e.g. `SI_USER` for a signal sent with `kill`.
+    pub fn code(&self) -> c_int {
+        self.code
+    }
+
+    /// Returns the process that sent the signal, or the child that changed
+    /// state for `SIGCHLD`.
+    pub fn pid(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// Returns the exit status or signal of the child for `SIGCHLD`.
+    pub fn status(&self) -> c_int {
+        self.status
+    }
+}
+
+impl Signals {
+    /// Starts receiving `signals`. `SIGKILL` and `SIGSTOP` cannot be caught.
+    pub fn new(signals: &[c_int]) -> io::Result<Signals> {
+        for &signal in signals {
+            if signal <= 0 || signal as usize >= MAX_SIGNAL {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid signal number",
+                ));
+            }
+        }
+
+        let (read, write) = crate::unix::pipe()?;
+        let mut restore = Restore {
+            write: unsafe { OwnedFd::from_raw_fd(write.into_raw_fd()) },
+            signals: Vec::new(),
+        };
+        for &signal in signals {
+            if restore.signals.contains(&signal) {
+                continue;
+            }
+            signal::subscribe(signal, Slot::Record, restore.write.as_raw_fd())?;
+            restore.signals.push(signal);
+        }
+        Ok(Signals {
+            fd: unsafe { OwnedFd::from_raw_fd(read.into_raw_fd()) },
+            restore,
+        })
+    }
+
+    /// Returns the next pending signal, or `WouldBlock` once all of them
+    /// have been received.
+    pub fn recv(&self) -> io::Result<SignalInfo> {
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        let len = mem::size_of::<libc::siginfo_t>();
+        let n = sys::cvt(unsafe {
+            libc::read(
+                self.as_raw_fd(),
+                &mut info as *mut libc::siginfo_t as *mut libc::c_void,
+                len,
+            )
+        })?;
+        // Records are written in one piece by the handler.
+        if n as usize != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "short signal record",
+            ));
+        }
+        unsafe {
+            Ok(SignalInfo {
+                signal: info.si_signo,
+                code: info.si_code,
+                pid: info.si_pid(),
+                uid: info.si_uid(),
+                status: info.si_status(),
+            })
+        }
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        self.restore.restore();
+    }
+}
+
+// Also run on drop, to undo a partially completed `Signals::new`.
+impl Drop for Restore {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+impl Restore {
+    fn restore(&mut self) {
+        for signal in self.signals.drain(..) {
+            signal::unsubscribe(signal, Slot::Record);
+        }
+    }
+}
+
+impl Evented for Signals {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for Signals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signals")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::os::unix::net;
+use std::path::Path;
+
+use crate::sys;
+#[cfg(target_os = "linux")]
+use crate::unix::UCred;
+use crate::unix::{addr, msg, EventedFd, SocketAddr, UnixRecvMeta};
+use crate::{Evented, Poll, PollOpt, Ready, Token};
+
+pub struct UnixStream(net::UnixStream);
+
+impl UnixStream {
+    /// Connects to the socket bound at `path` without blocking.
+    ///
+    /// Like `TcpStream::connect`, the connection may still be in progress
+    /// when this returns; writable readiness signals its completion.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<UnixStream> {
+        UnixStream::connect_addr(&SocketAddr::from_pathname(path)?)
+    }
+
+    /// Connects to the socket at `addr`, which may be an abstract address.
+    pub fn connect_addr(addr: &SocketAddr) -> io::Result<UnixStream> {
+        let fd = sys::socket_raw(libc::AF_UNIX, libc::SOCK_STREAM)?;
+        let stream = unsafe { UnixStream::from_raw_fd(fd) };
+        addr::connect(fd, addr)?;
+        Ok(stream)
+    }
+
+    pub fn from_std(stream: net::UnixStream) -> io::Result<UnixStream> {
+        stream.set_nonblocking(true)?;
+        Ok(UnixStream(stream))
+    }
+
+    /// Creates a pair of connected sockets.
+    pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
+        let (a, b) = net::UnixStream::pair()?;
+        Ok((UnixStream::from_std(a)?, UnixStream::from_std(b)?))
+    }
+
+    pub fn try_clone(&self) -> io::Result<UnixStream> {
+        Ok(UnixStream(self.0.try_clone()?))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.0.shutdown(how)
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.0.take_error()
+    }
+
+    /// Sends `buf` together with the file descriptors `fds` (`SCM_RIGHTS`).
+    /// The descriptors stay open in this process.
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+        msg::send_with_fds(self.as_raw_fd(), buf, fds)
+    }
+
+    /// Receives into `buf`, appending the file descriptors passed along with
+    /// the data to `fds`. They are received with close-on-exec set.
+    pub fn recv_with_fds(
+        &self,
+        buf: &mut [u8],
+        fds: &mut Vec<OwnedFd>,
+    ) -> io::Result<UnixRecvMeta> {
+        msg::recv(self.as_raw_fd(), buf, Some(fds))
+    }
+
+    /// Returns the credentials of the peer process at the time it connected
+    /// (`SO_PEERCRED`).
+    #[cfg(target_os = "linux")]
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        sys::getsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PEERCRED).map(UCred::from_raw)
+    }
+}
+
+impl Read for UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&self.0).read(buf)
+    }
+}
+
+impl Read for &UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&self.0).read(buf)
+    }
+}
+
+impl Write for UnixStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.0).flush()
+    }
+}
+
+impl Write for &UnixStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.0).flush()
+    }
+}
+
+impl Evented for UnixStream {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl fmt::Debug for UnixStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl IntoRawFd for UnixStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixStream {
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixStream {
+        UnixStream(net::UnixStream::from_raw_fd(fd))
+    }
+}
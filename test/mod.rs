@@ -5,24 +5,47 @@ extern crate fuchsia_zircon as zircon;
 
 pub use ports::localhost;
 
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_accept_many;
 mod test_close_on_drop;
 mod test_custom_evented;
 mod test_double_register;
 mod test_echo_server;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_evented_io;
 mod test_local_addr_ready;
 mod test_multicast;
 //mod test_oneshot;
 mod test_poll;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_process;
 mod test_register_deregister;
 // TODO: Behavior is different between 0.6 and 0.7 here
 //mod test_register_multiple_event_loops;
 mod test_reregister_without_poll;
+#[cfg(target_os = "linux")]
+mod test_sendfile;
 mod test_smoke;
+#[cfg(target_os = "linux")]
+mod test_splice;
 mod test_tcp;
 //mod test_tcp_level;
 mod test_tcp_shutdown;
+mod test_udp_demux;
 //mod test_udp_level;
 mod test_udp_socket;
+#[cfg(target_os = "linux")]
+mod test_unix_cred;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_unix_datagram;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_unix_pipe;
+#[cfg(target_os = "linux")]
+mod test_unix_seqpacket;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_unix_signals;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+mod test_unix_stream;
 mod test_write_then_drop;
 
 #[cfg(feature = "with-deprecated")]
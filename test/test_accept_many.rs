@@ -0,0 +1,126 @@
+use std::env;
+use std::io;
+use std::net;
+use std::os::unix::io::AsRawFd;
+use std::process::Command;
+
+use mio_compat::net::{FdReserve, TcpListener};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+
+const CHILD_ENV: &str = "MIO_COMPAT_ACCEPT_MANY_CHILD";
+
+fn connect_clients(l: &TcpListener, n: usize) -> Vec<net::TcpStream> {
+    let addr = l.local_addr().unwrap();
+    (0..n)
+        .map(|_| net::TcpStream::connect(addr).unwrap())
+        .collect()
+}
+
+#[test]
+fn accept_many() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    let _clients = connect_clients(&l, 5);
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    let mut conns = Vec::new();
+    assert_eq!(l.accept_many(&mut conns, 3).unwrap(), 3);
+    assert_eq!(l.accept_many(&mut conns, 3).unwrap(), 2);
+    assert_eq!(conns.len(), 5);
+    assert_eq!(
+        l.accept_many(&mut conns, 3).unwrap_err().kind(),
+        io::ErrorKind::WouldBlock
+    );
+}
+
+// Runs `emfile_child` in a separate process, since lowering RLIMIT_NOFILE
+// would affect every other test in this one.
+#[test]
+fn accept_many_emfile() {
+    let status = Command::new(env::current_exe().unwrap())
+        .args([
+            "test_accept_many::emfile_child",
+            "--exact",
+            "--ignored",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+#[ignore]
+fn emfile_child() {
+    const N: usize = 8;
+
+    assert!(
+        env::var_os(CHILD_ENV).is_some(),
+        "run through accept_many_emfile"
+    );
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    let _clients = connect_clients(&l, N);
+    let mut reserve = FdReserve::new().unwrap();
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    // Leave room for at most two more descriptors.
+    unsafe {
+        let free = libc::dup(l.as_raw_fd());
+        assert!(free >= 0);
+        libc::close(free);
+        let mut limit: libc::rlimit = std::mem::zeroed();
+        assert_eq!(libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit), 0);
+        limit.rlim_cur = (free + 2) as libc::rlim_t;
+        assert_eq!(libc::setrlimit(libc::RLIMIT_NOFILE, &limit), 0);
+    }
+
+    // Without a reserve the listener stops at EMFILE and stays readable,
+    // keeping the connections accepted before it.
+    let mut conns = Vec::new();
+    let err = l.accept_many(&mut conns, N).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EMFILE));
+    let accepted = conns.len();
+    assert!((1..N).contains(&accepted));
+    let err = l.accept_many(&mut conns, N).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EMFILE));
+
+    // With a reserve the remaining connections are shed and the listener is
+    // drained.
+    assert_eq!(
+        l.accept_many_with_reserve(&mut conns, N, &mut reserve)
+            .unwrap(),
+        0
+    );
+    assert_eq!(reserve.dropped(), N - accepted);
+    assert_eq!(conns.len(), accepted);
+    let err = l
+        .accept_many_with_reserve(&mut conns, N, &mut reserve)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    // New connections are reported again once descriptors are available.
+    conns.clear();
+    let _more = connect_clients(&l, 1);
+    poll.poll(&mut events, None).unwrap();
+    assert!(!events.is_empty());
+    // Asking for more would fail with EMFILE, as `accept` takes a
+    // descriptor before looking for a connection.
+    assert_eq!(l.accept_many(&mut conns, 1).unwrap(), 1);
+}
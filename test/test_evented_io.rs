@@ -0,0 +1,59 @@
+use mio_compat::unix::{EventedFd, EventedIo};
+use mio_compat::{Evented, Events, Poll, PollOpt, Ready, Token};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+#[test]
+pub fn evented_io_read_write() {
+    let (a, b) = UnixStream::pair().unwrap();
+    let mut a = EventedIo::new(a).unwrap();
+    let mut b = EventedIo::new(b).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&b, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 16];
+    assert_eq!(ErrorKind::WouldBlock, b.read(&mut buf).unwrap_err().kind());
+
+    a.write_all(b"hello").unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    let event = events.iter().next().unwrap();
+    assert_eq!(event.token(), Token(0));
+    assert!(event.readiness().is_readable());
+    assert_eq!((&b).read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    // Dropping the wrapper closes the descriptor.
+    drop(a);
+    assert_eq!(b.read(&mut buf).unwrap(), 0);
+    assert!(b.into_inner().take_error().unwrap().is_none());
+}
+
+#[test]
+pub fn evented_io_empty_interest() {
+    let (a, _b) = UnixStream::pair().unwrap();
+    let a = EventedIo::new(a).unwrap();
+    let poll = Poll::new().unwrap();
+
+    let err = poll
+        .register(&a, Token(0), Ready::empty(), PollOpt::edge())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let err = EventedFd(&a.as_raw_fd())
+        .register(&poll, Token(0), Ready::empty(), PollOpt::edge())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    a.register(&poll, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let err = a
+        .reregister(&poll, Token(0), Ready::empty(), PollOpt::edge())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
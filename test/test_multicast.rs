@@ -7,6 +7,8 @@ use bytes::{Buf, MutBuf, RingBuf, SliceBuf};
 use mio_compat::net::UdpSocket;
 use mio_compat::{Events, Poll, PollOpt, Ready, Token};
 use std::net::IpAddr;
+#[cfg(target_os = "linux")]
+use std::net::SocketAddr;
 use std::str;
 
 use log::{debug, info};
@@ -112,4 +114,98 @@ pub fn test_multicast() {
             }
         }
     }
+
+    #[cfg(target_os = "linux")]
+    multicast_options();
+}
+
+#[cfg(target_os = "linux")]
+fn recv_within(poll: &Poll, socket: &UdpSocket, buf: &mut [u8]) -> Option<(usize, SocketAddr)> {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    let mut events = Events::with_capacity(16);
+    loop {
+        match socket.recv_from(buf) {
+            Ok(res) => return Some(res),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, Some(Duration::from_millis(200)))
+                    .unwrap();
+                if events.is_empty() {
+                    return None;
+                }
+            }
+            Err(e) => panic!("recv_from failed: {}", e),
+        }
+    }
+}
+
+// Interface, loopback and source-specific membership options, checked as
+// part of `test_multicast`.
+#[cfg(target_os = "linux")]
+fn multicast_options() {
+    use std::ffi::CString;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let lo_name = CString::new("lo").unwrap();
+    let lo = unsafe { libc::if_nametoindex(lo_name.as_ptr()) };
+    assert!(lo > 0);
+    let loopback = Ipv4Addr::new(127, 0, 0, 1);
+
+    let poll = Poll::new().unwrap();
+
+    let rx = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+    let port = rx.local_addr().unwrap().port();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    tx.set_multicast_if_v4(&loopback).unwrap();
+    assert_eq!(tx.multicast_if_v4().unwrap(), loopback);
+    tx.set_multicast_loop_v4(true).unwrap();
+
+    // Any-source group joined by interface index.
+    let group = Ipv4Addr::new(227, 1, 1, 102);
+    rx.join_multicast_v4_index(&group, lo).unwrap();
+    tx.send_to(b"asm", &SocketAddr::new(group.into(), port))
+        .unwrap();
+    let mut buf = [0; 16];
+    let (n, from) = recv_within(&poll, &rx, &mut buf).expect("no datagram");
+    assert_eq!(&buf[..n], b"asm");
+    assert_eq!(from, tx.local_addr().unwrap());
+    rx.leave_multicast_v4_index(&group, lo).unwrap();
+
+    // Source-specific group: only datagrams from the joined source arrive.
+    let group = Ipv4Addr::new(232, 1, 1, 102);
+    let other = UdpSocket::bind(&"127.0.0.2:0".parse().unwrap()).unwrap();
+    other.set_multicast_if_v4(&loopback).unwrap();
+    rx.join_source_multicast_v4(&loopback, &group, &loopback)
+        .unwrap();
+    other
+        .send_to(b"other", &SocketAddr::new(group.into(), port))
+        .unwrap();
+    tx.send_to(b"ssm", &SocketAddr::new(group.into(), port))
+        .unwrap();
+    let (n, from) = recv_within(&poll, &rx, &mut buf).expect("no datagram");
+    assert_eq!(&buf[..n], b"ssm");
+    assert_eq!(from, tx.local_addr().unwrap());
+    rx.leave_source_multicast_v4(&loopback, &group, &loopback)
+        .unwrap();
+
+    // IPv6 may be unavailable in the test environment.
+    let tx6 = match UdpSocket::bind(&"[::1]:0".parse().unwrap()) {
+        Ok(tx6) => tx6,
+        Err(_) => return,
+    };
+    tx6.set_multicast_if_v6(lo).unwrap();
+    assert_eq!(tx6.multicast_if_v6().unwrap(), lo);
+    tx6.set_multicast_hops_v6(5).unwrap();
+    assert_eq!(tx6.multicast_hops_v6().unwrap(), 5);
+
+    let rx6 = UdpSocket::bind(&"[::]:0".parse().unwrap()).unwrap();
+    let group6: Ipv6Addr = "ff3e::8000:1".parse().unwrap();
+    rx6.join_source_multicast_v6(&Ipv6Addr::LOCALHOST, &group6, lo)
+        .unwrap();
+    rx6.leave_source_multicast_v6(&Ipv6Addr::LOCALHOST, &group6, lo)
+        .unwrap();
 }
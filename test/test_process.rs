@@ -0,0 +1,176 @@
+use mio_compat::process::{Child, ChildExit};
+use mio_compat::unix::Signals;
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const STDIN: Token = Token(0);
+const STDOUT: Token = Token(1);
+const EXIT: Token = Token(2);
+
+const DATA: &[u8] = &[42; 256 * 1024];
+
+#[test]
+pub fn process_stdio_and_exit() {
+    let mut child = Child::spawn(
+        Command::new("/bin/sh")
+            .args(["-c", "cat; exit 3"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped()),
+    )
+    .unwrap();
+    assert!(child.stderr.is_none());
+
+    let poll = Poll::new().unwrap();
+    poll.register(
+        child.stdin.as_ref().unwrap(),
+        STDIN,
+        Ready::writable(),
+        PollOpt::edge(),
+    )
+    .unwrap();
+    poll.register(
+        child.stdout.as_ref().unwrap(),
+        STDOUT,
+        Ready::readable(),
+        PollOpt::edge(),
+    )
+    .unwrap();
+    poll.register(&child.exit, EXIT, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut written = 0;
+    let mut output = Vec::new();
+    let mut buf = [0; 4096];
+    let mut status = None;
+    while status.is_none() || child.stdout.is_some() {
+        poll.poll(&mut events, None).unwrap();
+
+        for event in &events {
+            match event.token() {
+                STDIN => {
+                    let stdin = child.stdin.as_mut().unwrap();
+                    while written < DATA.len() {
+                        match stdin.write(&DATA[written..]) {
+                            Ok(n) => written += n,
+                            Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                            Err(e) => panic!("write failed: {}", e),
+                        }
+                    }
+                    if written == DATA.len() {
+                        poll.deregister(stdin).unwrap();
+                        child.stdin = None;
+                    }
+                }
+                STDOUT => loop {
+                    let stdout = child.stdout.as_mut().unwrap();
+                    match stdout.read(&mut buf) {
+                        Ok(0) => {
+                            poll.deregister(stdout).unwrap();
+                            child.stdout = None;
+                            break;
+                        }
+                        Ok(n) => output.extend_from_slice(&buf[..n]),
+                        Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                        Err(e) => panic!("read failed: {}", e),
+                    }
+                },
+                EXIT => status = child.exit.try_wait().unwrap(),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    assert_eq!(status.unwrap().code(), Some(3));
+    assert_eq!(output.len(), DATA.len());
+    assert!(output == DATA);
+}
+
+#[test]
+pub fn process_kill() {
+    let mut child = Child::spawn(Command::new("sleep").arg("10")).unwrap();
+    assert!(child.stdin.is_none() && child.stdout.is_none());
+
+    let poll = Poll::new().unwrap();
+    poll.register(&child.exit, EXIT, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    poll.poll(&mut events, Some(Duration::from_millis(100)))
+        .unwrap();
+    assert!(events.is_empty());
+    assert!(child.exit.try_wait().unwrap().is_none());
+
+    child.exit.kill().unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), EXIT);
+    let status = child.exit.try_wait().unwrap().unwrap();
+    assert_eq!(status.signal(), Some(libc::SIGKILL));
+}
+
+fn wait_exit(exit: &ChildExit) {
+    let poll = Poll::new().unwrap();
+    poll.register(exit, EXIT, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(1);
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), EXIT);
+}
+
+fn exited(pid: u32) -> bool {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    assert_eq!(
+        unsafe { libc::waitid(libc::P_PID, pid as _, &mut info, flags) },
+        0
+    );
+    unsafe { info.si_pid() != 0 }
+}
+
+#[test]
+pub fn process_sigchld_exit_before_watch() {
+    let child = Command::new("true").spawn().unwrap();
+    while !exited(child.id()) {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut exit = ChildExit::with_sigchld(child).unwrap();
+    wait_exit(&exit);
+    assert!(exit.try_wait().unwrap().unwrap().success());
+}
+
+#[test]
+pub fn process_sigchld_concurrent_children() {
+    let mut exits: Vec<_> = (0..4)
+        .map(|i| {
+            let child = Command::new("sleep")
+                .arg(format!("0.{}", i))
+                .spawn()
+                .unwrap();
+            ChildExit::with_sigchld(child).unwrap()
+        })
+        .collect();
+
+    for exit in &mut exits {
+        wait_exit(exit);
+        assert!(exit.try_wait().unwrap().unwrap().success());
+    }
+}
+
+#[test]
+pub fn process_sigchld_shared_with_signals() {
+    let signals = Signals::new(&[libc::SIGCHLD]).unwrap();
+    let child = Command::new("sleep").arg("0.1").spawn().unwrap();
+    let mut exit = ChildExit::with_sigchld(child).unwrap();
+
+    // Releasing `SIGCHLD` leaves the handler for the watches in place.
+    drop(signals);
+    wait_exit(&exit);
+    assert!(exit.try_wait().unwrap().unwrap().success());
+}
@@ -0,0 +1,62 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net;
+use std::thread;
+
+use mio_compat::net::TcpStream;
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use tempdir::TempDir;
+
+#[test]
+fn sendfile_large_file() {
+    const N: usize = 8 * 1024 * 1024;
+
+    let dir = TempDir::new("mio").unwrap();
+    let path = dir.path().join("sendfile");
+    let data: Vec<u8> = (0..N).map(|i| (i % 251) as u8).collect();
+    File::create(&path).unwrap().write_all(&data).unwrap();
+    let file = File::open(&path).unwrap();
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        let mut s = l.accept().unwrap().0;
+        let mut received = Vec::with_capacity(N);
+        s.read_to_end(&mut received).unwrap();
+        received
+    });
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let s = TcpStream::connect(&addr).unwrap();
+    poll.register(&s, Token(1), Ready::writable(), PollOpt::edge())
+        .unwrap();
+
+    let mut offset = 0;
+    while offset < N {
+        poll.poll(&mut events, None).unwrap();
+
+        for event in &events {
+            assert_eq!(event.token(), Token(1));
+            while offset < N {
+                match s.sendfile(&file, offset as u64, N - offset) {
+                    Ok(n) => {
+                        assert!(n > 0);
+                        offset += n;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(e) => panic!("sendfile failed: {}", e),
+                }
+            }
+        }
+    }
+    assert_eq!(s.sendfile(&file, N as u64, 1).unwrap(), 0);
+    assert_eq!(
+        s.sendfile(&file, u64::MAX, 1).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    drop(s);
+
+    assert!(t.join().unwrap() == data);
+}
@@ -0,0 +1,69 @@
+use std::io::{self, Read, Write};
+use std::net;
+use std::thread;
+
+use mio_compat::net::{Splice, TcpStream};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+
+const SRC: Token = Token(0);
+const DST: Token = Token(1);
+
+fn connected_pair(l: &net::TcpListener) -> (TcpStream, net::TcpStream) {
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (peer, _) = l.accept().unwrap();
+    (s, peer)
+}
+
+#[test]
+fn splice_forward() {
+    const N: usize = 4 * 1024 * 1024;
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let (from, mut client) = connected_pair(&l);
+    let (to, mut upstream) = connected_pair(&l);
+
+    let data: Vec<u8> = (0..N).map(|i| (i % 253) as u8).collect();
+    let expected = data.clone();
+    let writer = thread::spawn(move || {
+        client.write_all(&data).unwrap();
+    });
+    let reader = thread::spawn(move || {
+        let mut received = Vec::with_capacity(N);
+        upstream.read_to_end(&mut received).unwrap();
+        received
+    });
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&from, SRC, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    poll.register(&to, DST, Ready::writable(), PollOpt::edge())
+        .unwrap();
+
+    let mut splice = Splice::new().unwrap();
+    // Keep the pipe small so the destination fills up regularly.
+    splice.set_capacity(4096).unwrap();
+
+    let mut total = 0;
+    'outer: loop {
+        poll.poll(&mut events, None).unwrap();
+
+        for _ in &events {
+            loop {
+                match splice.forward(&from, &to) {
+                    Ok(0) => break 'outer,
+                    Ok(n) => total += n,
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(e) => panic!("forward failed: {}", e),
+                }
+            }
+        }
+    }
+
+    assert_eq!(splice.pending(), 0);
+    assert_eq!(total, N);
+    drop(to);
+
+    writer.join().unwrap();
+    assert!(reader.join().unwrap() == expected);
+}
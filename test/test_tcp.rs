@@ -10,6 +10,8 @@ use net2::{self, TcpStreamExt};
 
 use crate::{TryRead, TryWrite};
 use iovec::IoVec;
+#[cfg(target_os = "linux")]
+use mio_compat::net::MsgFlags;
 use mio_compat::net::{TcpListener, TcpStream};
 use mio_compat::{Events, Poll, PollOpt, Ready, Token};
 
@@ -728,3 +730,159 @@ fn write_error() {
         }
     }
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+fn send_recv_with_flags() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let peer = TcpStream::from_stream(l.accept().unwrap().0).unwrap();
+
+    poll.register(&peer, Token(1), Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    // The header is held back with MSG_MORE and goes out with the body.
+    let more = MsgFlags::more() | MsgFlags::nosignal();
+    assert!(more.is_more() && more.is_nosignal() && !more.is_peek());
+    assert_eq!(s.send_with_flags(b"head:", more).unwrap(), 5);
+    assert_eq!(s.send_with_flags(b"body", MsgFlags::nosignal()).unwrap(), 4);
+
+    let mut buf = [0; 9];
+    let mut amt = 0;
+    while amt < buf.len() {
+        poll.poll(&mut events, None).unwrap();
+        match peer.recv_with_flags(&mut buf, MsgFlags::peek()) {
+            Ok(n) => amt = n,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("unexpected error {:?}", e),
+        }
+    }
+    assert_eq!(&buf, b"head:body");
+
+    // Peeked data is still there to be received.
+    let mut buf = [0; 9];
+    assert_eq!(
+        peer.recv_with_flags(&mut buf, MsgFlags::empty()).unwrap(),
+        9
+    );
+    assert_eq!(&buf, b"head:body");
+    assert_eq!(
+        peer.recv_with_flags(&mut buf, MsgFlags::empty())
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::WouldBlock
+    );
+
+    // Writing to a closed connection reports EPIPE instead of raising
+    // SIGPIPE.
+    drop(peer);
+    let res = loop {
+        match s.send_with_flags(&[0; 1024], MsgFlags::nosignal()) {
+            Ok(_) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => break e,
+        }
+    };
+    assert!(
+        res.kind() == io::ErrorKind::BrokenPipe || res.kind() == io::ErrorKind::ConnectionReset,
+        "unexpected error {:?}",
+        res
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn connect_fastopen() {
+    const MSG: &[u8] = b"hello fast open";
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    l.set_fastopen(16).unwrap();
+    assert_eq!(l.fastopen().unwrap(), 16);
+    let addr = l.local_addr().unwrap();
+
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    // Cookies are only handed out with both client and server Fast Open
+    // enabled; the default is client only.
+    let mode = std::fs::read_to_string("/proc/sys/net/ipv4/tcp_fastopen")
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok());
+    let enabled = matches!(mode, Some(mode) if mode & 3 == 3);
+
+    // The first connection only obtains a cookie, the second one carries
+    // the data in the SYN. Either way the data must arrive exactly once.
+    for i in 0..2 {
+        let (mut s, sent) = TcpStream::connect_fastopen(&addr, MSG).unwrap();
+        assert!(sent <= MSG.len());
+        if i == 1 && enabled {
+            assert_eq!(sent, MSG.len());
+        }
+        poll.register(&s, Token(1), Ready::writable(), PollOpt::edge())
+            .unwrap();
+
+        let mut written = sent;
+        let mut server = None;
+        let mut received = Vec::new();
+        while received.len() < MSG.len() {
+            poll.poll(&mut events, None).unwrap();
+
+            for event in &events {
+                match event.token() {
+                    Token(0) => {
+                        let (conn, _) = l.accept().unwrap();
+                        poll.register(&conn, Token(2), Ready::readable(), PollOpt::edge())
+                            .unwrap();
+                        server = Some(conn);
+                    }
+                    Token(1) if written < MSG.len() => {
+                        written += s.write(&MSG[written..]).unwrap();
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(ref mut conn) = server {
+                let mut buf = [0; 64];
+                while let Some(n) = conn.try_read(&mut buf).unwrap() {
+                    if n == 0 {
+                        break;
+                    }
+                    received.extend_from_slice(&buf[..n]);
+                }
+            }
+        }
+
+        assert_eq!(&received[..], MSG);
+        if i == 1 && enabled {
+            assert!(syn_data_acked(&s));
+        }
+    }
+}
+
+// Whether the peer acknowledged data sent in the SYN, from the
+// `tcpi_options` byte of `TCP_INFO`.
+#[cfg(target_os = "linux")]
+fn syn_data_acked(s: &TcpStream) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    const TCPI_OPT_SYN_DATA: u8 = 32;
+    let mut info = [0u8; 8];
+    let mut len = info.len() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockopt(
+            s.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    assert_eq!(res, 0);
+    info[5] & TCPI_OPT_SYN_DATA != 0
+}
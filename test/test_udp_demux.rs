@@ -0,0 +1,102 @@
+use mio_compat::net::{UdpDemux, UdpSocket};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+const DEMUX: Token = Token(0);
+const SESSION: Token = Token(1);
+const PEER: Token = Token(2);
+
+fn wait_for(poll: &Poll, events: &mut Events, token: Token) {
+    loop {
+        poll.poll(events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty(), "timed out waiting for {:?}", token);
+        if events.iter().any(|event| event.token() == token) {
+            return;
+        }
+    }
+}
+
+#[test]
+pub fn udp_demux() {
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let demux = UdpDemux::new(socket, 2);
+    let addr = demux.local_addr().unwrap();
+
+    let a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&demux, DEMUX, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    assert_eq!(ErrorKind::WouldBlock, demux.accept().unwrap_err().kind());
+
+    assert_eq!(demux.dropped(), 0);
+    a.send_to(b"a1", &addr).unwrap();
+    wait_for(&poll, &mut events, DEMUX);
+    let (session_a, peer) = demux.accept().unwrap();
+    assert_eq!(peer, a.local_addr().unwrap());
+    assert_eq!(session_a.peer_addr(), peer);
+    assert_eq!(ErrorKind::WouldBlock, demux.accept().unwrap_err().kind());
+    poll.register(&session_a, SESSION, Ready::readable(), PollOpt::edge())
+        .unwrap();
+
+    // Datagrams from a known peer are queued on its session while looking
+    // for new peers.
+    a.send_to(b"a2", &addr).unwrap();
+    b.send_to(b"b1", &addr).unwrap();
+    wait_for(&poll, &mut events, DEMUX);
+    let (session_b, peer) = demux.accept().unwrap();
+    assert_eq!(peer, b.local_addr().unwrap());
+    assert_eq!(ErrorKind::WouldBlock, demux.accept().unwrap_err().kind());
+    wait_for(&poll, &mut events, SESSION);
+
+    let mut buf = [0; 16];
+    assert_eq!(session_a.recv(&mut buf).unwrap(), (2, false));
+    assert_eq!(&buf[..2], b"a1");
+    assert_eq!(session_a.recv(&mut buf).unwrap(), (2, false));
+    assert_eq!(&buf[..2], b"a2");
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        session_a.recv(&mut buf).unwrap_err().kind()
+    );
+    assert_eq!(session_b.recv(&mut buf).unwrap(), (2, false));
+    assert_eq!(&buf[..2], b"b1");
+
+    // Each session queues at most two datagrams.
+    for msg in &[b"a3", b"a4", b"a5"] {
+        a.send_to(*msg, &addr).unwrap();
+    }
+    wait_for(&poll, &mut events, DEMUX);
+    assert_eq!(ErrorKind::WouldBlock, demux.accept().unwrap_err().kind());
+    assert_eq!(demux.dropped(), 1);
+    assert_eq!(session_a.recv(&mut buf).unwrap(), (2, false));
+    assert_eq!(&buf[..2], b"a3");
+    assert_eq!(session_a.recv(&mut buf).unwrap(), (2, false));
+    assert_eq!(&buf[..2], b"a4");
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        session_a.recv(&mut buf).unwrap_err().kind()
+    );
+
+    // Replies go out of the shared socket.
+    poll.register(&b, PEER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    session_b.send(b"pong").unwrap();
+    wait_for(&poll, &mut events, PEER);
+    let (n, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"pong");
+    assert_eq!(from, addr);
+
+    // Once its session is dropped, a peer is accepted again.
+    drop(session_b);
+    b.send_to(b"b2", &addr).unwrap();
+    wait_for(&poll, &mut events, DEMUX);
+    let (session_b, peer) = demux.accept().unwrap();
+    assert_eq!(peer, b.local_addr().unwrap());
+    // A datagram longer than the buffer is truncated.
+    assert_eq!(session_b.recv(&mut buf[..1]).unwrap(), (1, true));
+    assert_eq!(&buf[..1], b"b");
+}
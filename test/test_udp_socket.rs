@@ -8,6 +8,8 @@ use std::time;
 
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 use iovec::IoVec;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use mio_compat::net::MsgFlags;
 
 use log::{debug, info};
 
@@ -262,3 +264,530 @@ pub fn test_udp_socket_send_recv_bufs() {
         }
     }
 }
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+#[test]
+pub fn test_udp_socket_recv_with_flags() {
+    let (tx, rx) = connected_sockets();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 5];
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        rx.recv_with_flags(&mut buf, MsgFlags::peek())
+            .unwrap_err()
+            .kind()
+    );
+
+    tx.send_with_flags(b"hello world", MsgFlags::empty())
+        .unwrap();
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    // Peeking leaves the datagram queued.
+    assert_eq!(rx.recv_with_flags(&mut buf, MsgFlags::peek()).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    // With MSG_TRUNC the full length is reported even though only part of
+    // the datagram fits in the buffer.
+    let mut small = [0; 4];
+    assert_eq!(
+        rx.recv_with_flags(&mut small, MsgFlags::trunc()).unwrap(),
+        11
+    );
+    assert_eq!(&small, b"hell");
+
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        rx.recv_with_flags(&mut buf, MsgFlags::empty())
+            .unwrap_err()
+            .kind()
+    );
+}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+#[test]
+pub fn test_udp_socket_send_to_recv_from_bufs() {
+    let tx = UdpSocket::bind(&localhost()).unwrap();
+    let rx = UdpSocket::bind(&localhost()).unwrap();
+    let tx_addr = tx.local_addr().unwrap();
+    let rx_addr = rx.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let write_bufs: Vec<_> = vec![b"hello, " as &[u8], b"world"]
+        .into_iter()
+        .flat_map(IoVec::from_bytes)
+        .collect();
+    assert_eq!(tx.send_to_bufs(&write_bufs, &rx_addr).unwrap(), 12);
+    assert_eq!(tx.send_to_bufs(&write_bufs, &rx_addr).unwrap(), 12);
+
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    let mut a = [0u8; 4];
+    let mut b = [0u8; 10];
+    {
+        let mut read_bufs: Vec<&mut IoVec> = vec![(&mut a[..]).into(), (&mut b[..]).into()];
+        let meta = rx.recv_from_bufs(&mut read_bufs).unwrap();
+        assert_eq!(meta.len(), 12);
+        assert_eq!(meta.addr(), tx_addr);
+        assert!(!meta.is_truncated());
+    }
+    assert_eq!(&a, b"hell");
+    assert_eq!(&b[..8], b"o, world");
+
+    // The second datagram does not fit and is reported as truncated.
+    let mut a = [0u8; 4];
+    let mut b = [0u8; 2];
+    {
+        let mut read_bufs: Vec<&mut IoVec> = vec![(&mut a[..]).into(), (&mut b[..]).into()];
+        let meta = rx.recv_from_bufs(&mut read_bufs).unwrap();
+        assert_eq!(meta.len(), 6);
+        assert!(meta.is_truncated());
+    }
+    assert_eq!(&a, b"hell");
+    assert_eq!(&b, b"o,");
+
+    let mut read_bufs: Vec<&mut IoVec> = vec![(&mut a[..]).into()];
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        rx.recv_from_bufs(&mut read_bufs).unwrap_err().kind()
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_udp_socket_send_recv_many() {
+    let tx = UdpSocket::bind(&localhost()).unwrap();
+    let rx = UdpSocket::bind(&localhost()).unwrap();
+    let tx_addr = tx.local_addr().unwrap();
+    let rx_addr = rx.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    // More than one system call's worth of datagrams.
+    let msgs: Vec<Vec<u8>> = (0..40u8).map(|i| vec![i; 1 + i as usize]).collect();
+    let bufs: Vec<&[u8]> = msgs.iter().map(|msg| &msg[..]).collect();
+    let targets = vec![rx_addr; bufs.len()];
+    assert_eq!(tx.send_to_many(&bufs, &targets).unwrap(), 40);
+    assert_eq!(
+        tx.send_to_many(&bufs, &targets[..1]).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    let mut storage = [[0u8; 64]; 36];
+    let mut meta = Vec::new();
+    let mut received = Vec::new();
+    loop {
+        let mut recv_bufs: Vec<&mut [u8]> = storage.iter_mut().map(|b| &mut b[..]).collect();
+        match rx.recv_many(&mut recv_bufs, &mut meta) {
+            Ok(n) => {
+                assert!(n > 0 && n <= 36);
+                assert_eq!(meta.len(), n);
+                for (buf, meta) in recv_bufs.iter().zip(meta.iter()) {
+                    assert_eq!(meta.addr(), tx_addr);
+                    received.push(buf[..meta.len()].to_vec());
+                }
+            }
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) => panic!("recv_many failed: {}", e),
+        }
+    }
+    assert_eq!(received, msgs);
+
+    // Connected sockets can send without addresses.
+    tx.connect(rx_addr).unwrap();
+    assert_eq!(tx.send_many(&bufs[..3]).unwrap(), 3);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_udp_socket_segments() {
+    const SEGMENT: usize = 100;
+
+    let tx = UdpSocket::bind(&localhost()).unwrap();
+    let rx = UdpSocket::bind(&localhost()).unwrap();
+    let rx_addr = rx.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let gro = rx.set_gro(true).is_ok();
+    if gro {
+        assert!(rx.gro().unwrap());
+    }
+
+    let data: Vec<u8> = (0..(4 * SEGMENT + 50)).map(|i| i as u8).collect();
+    assert_eq!(
+        tx.send_to_segments(&data, SEGMENT, &rx_addr).unwrap(),
+        data.len()
+    );
+    assert_eq!(
+        tx.send_to_segments(&data, 0, &rx_addr).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    if tx.supports_segmentation() {
+        // More segments than the kernel accepts is an error, not a reason
+        // to send them one by one.
+        assert_eq!(
+            tx.send_to_segments(&[0; 1000], 1, &rx_addr)
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::EINVAL)
+        );
+    }
+
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    // Split what arrives back into segments, whether or not the kernel
+    // coalesced them.
+    let mut segments = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let meta = {
+            let mut bufs: Vec<&mut IoVec> = vec![(&mut buf[..]).into()];
+            match rx.recv_from_bufs(&mut bufs) {
+                Ok(meta) => meta,
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => panic!("recv failed: {}", e),
+            }
+        };
+        let size = match meta.segment_size() {
+            Some(size) => {
+                assert!(gro);
+                size
+            }
+            None => meta.len(),
+        };
+        assert!(size <= SEGMENT);
+        segments.extend(buf[..meta.len()].chunks(size).map(|s| s.to_vec()));
+    }
+
+    let expected: Vec<Vec<u8>> = data.chunks(SEGMENT).map(|s| s.to_vec()).collect();
+    assert_eq!(segments, expected);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_udp_socket_pktinfo() {
+    use mio_compat::net::SendMeta;
+    use std::net::{IpAddr, SocketAddr};
+
+    let rx = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+    rx.set_recv_pktinfo(true).unwrap();
+    let port = rx.local_addr().unwrap().port();
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let tx_addr = tx.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    poll.register(&tx, SENDER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    for dst in &["127.0.0.1", "127.0.0.2", "127.0.0.3"] {
+        let dst: IpAddr = dst.parse().unwrap();
+        tx.send_to(b"ping", &SocketAddr::new(dst, port)).unwrap();
+
+        let mut buf = [0; 16];
+        let meta = loop {
+            match rx.recv_msg(&mut buf) {
+                Ok(meta) => break meta,
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                    poll.poll(&mut events, None).unwrap();
+                }
+                Err(e) => panic!("recv_msg failed: {}", e),
+            }
+        };
+        assert_eq!(&buf[..meta.len()], b"ping");
+        assert_eq!(meta.addr(), tx_addr);
+        assert_eq!(meta.dst_addr(), Some(dst));
+        let interface = meta.interface().unwrap();
+        assert!(interface > 0);
+
+        // Reply from the address the request was sent to.
+        let reply = SendMeta::new().src_addr(dst).interface(interface);
+        rx.send_msg(b"pong", &tx_addr, &reply).unwrap();
+        let (n, from) = loop {
+            match tx.recv_from(&mut buf) {
+                Ok(res) => break res,
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                    poll.poll(&mut events, None).unwrap();
+                }
+                Err(e) => panic!("recv_from failed: {}", e),
+            }
+        };
+        assert_eq!(&buf[..n], b"pong");
+        assert_eq!(from, SocketAddr::new(dst, port));
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_recv_timestamp_tos_ttl() {
+    use std::time::{Duration, SystemTime};
+
+    let rx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    rx.set_recv_timestamp(true).unwrap();
+    rx.set_recv_tos(true).unwrap();
+    rx.set_recv_ttl(true).unwrap();
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    tx.set_ttl(42).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let sent_at = SystemTime::now();
+    tx.send_to(b"ping", &rx.local_addr().unwrap()).unwrap();
+
+    let mut buf = [0; 16];
+    let meta = loop {
+        match rx.recv_msg(&mut buf) {
+            Ok(meta) => break meta,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_msg failed: {}", e),
+        }
+    };
+    assert_eq!(&buf[..meta.len()], b"ping");
+    assert_eq!(meta.ttl(), Some(42));
+    assert_eq!(meta.tos(), Some(0));
+    let timestamp = meta.timestamp().unwrap();
+    let skew = timestamp
+        .duration_since(sent_at)
+        .unwrap_or_else(|e| e.duration());
+    assert!(skew < Duration::from_secs(5), "skew {:?}", skew);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_ecn() {
+    use mio_compat::net::{Ecn, SendMeta};
+
+    for addr in &["127.0.0.1:0", "[::1]:0"] {
+        let rx = match UdpSocket::bind(&addr.parse().unwrap()) {
+            Ok(rx) => rx,
+            // IPv6 may be unavailable in the test environment.
+            Err(_) => continue,
+        };
+        rx.set_recv_tos(true).unwrap();
+        let rx_addr = rx.local_addr().unwrap();
+        let tx = UdpSocket::bind(&addr.parse().unwrap()).unwrap();
+
+        let poll = Poll::new().unwrap();
+        poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+            .unwrap();
+        let mut events = Events::with_capacity(16);
+
+        for &ecn in &[Ecn::NotEct, Ecn::Ect0, Ecn::Ect1, Ecn::Ce] {
+            tx.send_msg(b"ping", &rx_addr, &SendMeta::new().ecn(ecn))
+                .unwrap();
+
+            let mut buf = [0; 16];
+            let meta = loop {
+                match rx.recv_msg(&mut buf) {
+                    Ok(meta) => break meta,
+                    Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                        poll.poll(&mut events, None).unwrap();
+                    }
+                    Err(e) => panic!("recv_msg failed: {}", e),
+                }
+            };
+            assert_eq!(&buf[..meta.len()], b"ping");
+            assert_eq!(meta.ecn(), Some(ecn));
+        }
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_ecn_keeps_dscp() {
+    use mio_compat::net::{Ecn, SendMeta};
+
+    for addr in &["127.0.0.1:0", "[::1]:0"] {
+        let rx = match UdpSocket::bind(&addr.parse().unwrap()) {
+            Ok(rx) => rx,
+            Err(_) => continue,
+        };
+        rx.set_recv_tos(true).unwrap();
+        let rx_addr = rx.local_addr().unwrap();
+        let tx = UdpSocket::bind(&addr.parse().unwrap()).unwrap();
+        // Expedited forwarding.
+        tx.set_tos(0xb8).unwrap();
+
+        let poll = Poll::new().unwrap();
+        poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+            .unwrap();
+        let mut events = Events::with_capacity(16);
+
+        tx.send_msg(b"ping", &rx_addr, &SendMeta::new().ecn(Ecn::Ect0))
+            .unwrap();
+
+        let mut buf = [0; 16];
+        let meta = loop {
+            match rx.recv_msg(&mut buf) {
+                Ok(meta) => break meta,
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                    poll.poll(&mut events, None).unwrap();
+                }
+                Err(e) => panic!("recv_msg failed: {}", e),
+            }
+        };
+        assert_eq!(meta.tos(), Some(0xba));
+        assert_eq!(meta.ecn(), Some(Ecn::Ect0));
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_peek() {
+    let rx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 5];
+    assert_eq!(ErrorKind::WouldBlock, rx.peek(&mut buf).unwrap_err().kind());
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        rx.peek_from(&mut buf).unwrap_err().kind()
+    );
+    assert_eq!(ErrorKind::WouldBlock, rx.peek_len().unwrap_err().kind());
+
+    tx.send_to(b"hello world", &rx.local_addr().unwrap())
+        .unwrap();
+    while events.is_empty() {
+        poll.poll(&mut events, None).unwrap();
+    }
+
+    assert_eq!(rx.peek_len().unwrap(), 11);
+    assert_eq!(
+        rx.peek_from(&mut buf).unwrap(),
+        (5, tx.local_addr().unwrap())
+    );
+    assert_eq!(&buf, b"hello");
+    assert_eq!(rx.peek(&mut buf).unwrap(), 5);
+
+    // The datagram is still queued in full.
+    let mut full = [0; 16];
+    assert_eq!(
+        rx.recv_from(&mut full).unwrap(),
+        (11, tx.local_addr().unwrap())
+    );
+    assert_eq!(&full[..11], b"hello world");
+    assert_eq!(ErrorKind::WouldBlock, rx.peek_len().unwrap_err().kind());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_disconnect() {
+    let (tx, rx) = connected_sockets();
+    let other = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    // Once disconnected, datagrams from other peers are delivered too.
+    rx.disconnect().unwrap();
+    assert!(rx.send(b"nowhere").is_err());
+    other.send_to(b"hello", &rx.local_addr().unwrap()).unwrap();
+    tx.send(b"world").unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        let mut buf = [0; 16];
+        match rx.recv_from(&mut buf) {
+            Ok((n, addr)) => received.push((buf[..n].to_vec(), addr)),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_from failed: {}", e),
+        }
+    }
+    assert_eq!(
+        received,
+        vec![
+            (b"hello".to_vec(), other.local_addr().unwrap()),
+            (b"world".to_vec(), tx.local_addr().unwrap()),
+        ]
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_udp_socket_recv_err() {
+    use mio_compat::unix::UnixReady;
+
+    let closed = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap())
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    tx.set_recv_err(true).unwrap();
+    let mut buf = [0; 16];
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        tx.recv_err(&mut buf).unwrap_err().kind()
+    );
+
+    let poll = Poll::new().unwrap();
+    poll.register(&tx, SENDER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    tx.send_to(b"ping", &closed).unwrap();
+    loop {
+        poll.poll(&mut events, None).unwrap();
+        if events
+            .iter()
+            .any(|event| UnixReady::from(event.readiness()).is_error())
+        {
+            break;
+        }
+    }
+
+    let err = tx.recv_err(&mut buf).unwrap();
+    assert_eq!(err.error().kind(), ErrorKind::ConnectionRefused);
+    assert!(err.is_icmp());
+    // Destination unreachable, port unreachable.
+    assert_eq!((err.icmp_type(), err.icmp_code()), (3, 3));
+    assert_eq!(err.dst_addr(), closed);
+    assert_eq!(err.offender().map(|addr| addr.ip()), Some(closed.ip()));
+    assert_eq!(&buf[..err.len()], b"ping");
+
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        tx.recv_err(&mut buf).unwrap_err().kind()
+    );
+}
@@ -0,0 +1,76 @@
+use mio_compat::unix::{UnixDatagram, UnixListener, UnixStream};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::ErrorKind;
+use tempdir::TempDir;
+
+#[test]
+pub fn unix_stream_pair_peer_cred() {
+    let (a, b) = UnixStream::pair().unwrap();
+    for stream in &[a, b] {
+        let cred = stream.peer_cred().unwrap();
+        assert_eq!(cred.pid(), unsafe { libc::getpid() });
+        assert_eq!(cred.uid(), unsafe { libc::getuid() });
+        assert_eq!(cred.gid(), unsafe { libc::getgid() });
+    }
+}
+
+#[test]
+pub fn unix_cred_forked_child() {
+    let dir = TempDir::new("mio-compat").unwrap();
+    let path = dir.path().join("sock");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let (tx, rx) = UnixDatagram::pair().unwrap();
+    assert!(!rx.passcred().unwrap());
+    rx.set_passcred(true).unwrap();
+    assert!(rx.passcred().unwrap());
+
+    let child = unsafe { libc::fork() };
+    assert!(child >= 0);
+    if child == 0 {
+        // Stick to plain system calls in the child of a threaded process.
+        let status = match UnixStream::connect(&path) {
+            Ok(_) if tx.send(b"hello").is_ok() => 0,
+            _ => 1,
+        };
+        unsafe { libc::_exit(status) };
+    }
+
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    let poll = Poll::new().unwrap();
+    poll.register(&listener, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    poll.register(&rx, Token(1), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let stream = loop {
+        match listener.accept() {
+            Ok((stream, _)) => break stream,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("accept failed: {}", e),
+        }
+    };
+    let cred = stream.peer_cred().unwrap();
+    assert_eq!(cred.pid(), child);
+    assert_eq!(cred.uid(), unsafe { libc::getuid() });
+    assert_eq!(cred.gid(), unsafe { libc::getgid() });
+
+    let mut buf = [0; 16];
+    let meta = loop {
+        match rx.recv_with_cred(&mut buf) {
+            Ok(meta) => break meta,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_with_cred failed: {}", e),
+        }
+    };
+    assert_eq!(&buf[..meta.len()], b"hello");
+    assert_eq!(meta.cred(), Some(cred));
+}
@@ -0,0 +1,154 @@
+use mio_compat::unix::UnixDatagram;
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::ErrorKind;
+use std::net::Shutdown;
+use tempdir::TempDir;
+
+const SERVER: Token = Token(0);
+
+#[test]
+pub fn unix_datagram_send_to_recv_from() {
+    let dir = TempDir::new("mio-compat").unwrap();
+    let server_path = dir.path().join("server");
+    let client_path = dir.path().join("client");
+
+    let server = UnixDatagram::bind(&server_path).unwrap();
+    let client = UnixDatagram::bind(&client_path).unwrap();
+    let unbound = UnixDatagram::unbound().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&server, SERVER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 16];
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        server.recv_from(&mut buf).unwrap_err().kind()
+    );
+
+    client.send_to(b"named", &server_path).unwrap();
+    unbound.send_to(b"unnamed", &server_path).unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        match server.recv_from(&mut buf) {
+            Ok((n, addr)) => received.push((buf[..n].to_vec(), addr)),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_from failed: {}", e),
+        }
+    }
+    assert_eq!(received[0].0, b"named");
+    assert_eq!(received[0].1.as_pathname(), Some(&*client_path));
+    assert_eq!(received[1].0, b"unnamed");
+    assert!(received[1].1.is_unnamed());
+
+    // A connected socket can use send and recv.
+    client.connect(&server_path).unwrap();
+    assert_eq!(
+        client.peer_addr().unwrap().as_pathname(),
+        Some(&*server_path)
+    );
+    client.send(b"connected").unwrap();
+    let n = loop {
+        match server.recv(&mut buf) {
+            Ok(n) => break n,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv failed: {}", e),
+        }
+    };
+    assert_eq!(&buf[..n], b"connected");
+}
+
+#[test]
+pub fn unix_datagram_pair_shutdown() {
+    let (a, b) = UnixDatagram::pair().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&b, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    a.send(b"ping").unwrap();
+    let mut buf = [0; 16];
+    let n = loop {
+        match b.recv(&mut buf) {
+            Ok(n) => break n,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv failed: {}", e),
+        }
+    };
+    assert_eq!(&buf[..n], b"ping");
+
+    a.shutdown(Shutdown::Write).unwrap();
+    assert!(a.send(b"pong").is_err());
+    assert!(a.take_error().unwrap().is_none());
+}
+
+#[test]
+pub fn unix_datagram_pass_fds_truncated() {
+    use std::mem;
+    use std::os::unix::io::AsRawFd;
+
+    let (a, b) = UnixDatagram::pair().unwrap();
+
+    // More descriptors than `recv_with_fds` has room for, sent with a raw
+    // `sendmsg` since `send_with_fds` refuses them.
+    let fds = vec![a.as_raw_fd(); 100];
+    let payload = mem::size_of_val(&fds[..]) as libc::c_uint;
+    let mut control = vec![0u64; unsafe { libc::CMSG_SPACE(payload) } as usize / 8 + 1];
+    let data = b"fds";
+    let mut iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+    unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = libc::CMSG_SPACE(payload) as _;
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(payload) as _;
+        std::ptr::copy_nonoverlapping(
+            fds.as_ptr(),
+            libc::CMSG_DATA(cmsg) as *mut libc::c_int,
+            fds.len(),
+        );
+        assert_eq!(libc::sendmsg(a.as_raw_fd(), &msg, 0), data.len() as isize);
+    }
+
+    let poll = Poll::new().unwrap();
+    poll.register(&b, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 2];
+    let mut received = Vec::new();
+    let meta = loop {
+        match b.recv_with_fds(&mut buf, &mut received) {
+            Ok(meta) => break meta,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_with_fds failed: {}", e),
+        }
+    };
+    assert_eq!(meta.len(), 2);
+    assert!(meta.is_truncated());
+    assert!(meta.fds_truncated());
+    assert!(!received.is_empty() && received.len() < fds.len());
+
+    assert_eq!(
+        ErrorKind::InvalidInput,
+        a.send_with_fds(b"fds", &fds).unwrap_err().kind()
+    );
+}
@@ -0,0 +1,131 @@
+use mio_compat::unix::{self, PipeReader, PipeWriter, UnixReady};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const DATA: &[u8] = &[42; 256 * 1024];
+
+#[test]
+pub fn unix_pipe_hup() {
+    let (mut reader, mut writer) = unix::pipe().unwrap();
+    let poll = Poll::new().unwrap();
+    poll.register(&reader, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 16];
+    assert_eq!(
+        ErrorKind::WouldBlock,
+        reader.read(&mut buf).unwrap_err().kind()
+    );
+    writer.write_all(b"hello").unwrap();
+    drop(writer);
+
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    let event = events.iter().next().unwrap();
+    assert!(event.readiness().is_readable());
+    assert!(UnixReady::from(event.readiness()).is_hup());
+
+    assert_eq!(reader.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+pub fn unix_pipe_broken_pipe() {
+    let (reader, mut writer) = unix::pipe().unwrap();
+    drop(reader);
+    assert_eq!(
+        ErrorKind::BrokenPipe,
+        writer.write(b"x").unwrap_err().kind()
+    );
+
+    // The test harness ignores SIGPIPE, so check with the default
+    // disposition, which would kill the child, in a forked child.
+    let (reader, mut writer) = unix::pipe().unwrap();
+    drop(reader);
+    let child = unsafe { libc::fork() };
+    assert!(child >= 0);
+    if child == 0 {
+        let status = unsafe {
+            libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+            match writer.write(b"x") {
+                Err(ref e) if e.raw_os_error() == Some(libc::EPIPE) => 0,
+                _ => 1,
+            }
+        };
+        unsafe { libc::_exit(status) };
+    }
+
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+}
+
+#[test]
+pub fn unix_pipe_subprocess() {
+    let mut child = Command::new("/bin/cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = Some(PipeWriter::from_stdin(child.stdin.take().unwrap()).unwrap());
+    let mut stdout = PipeReader::from_stdout(child.stdout.take().unwrap()).unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(
+        stdin.as_ref().unwrap(),
+        Token(0),
+        Ready::writable(),
+        PollOpt::edge(),
+    )
+    .unwrap();
+    poll.register(&stdout, Token(1), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut written = 0;
+    let mut output = Vec::new();
+    let mut buf = [0; 4096];
+    let mut eof = false;
+    while !eof {
+        poll.poll(&mut events, None).unwrap();
+
+        for event in &events {
+            match event.token() {
+                Token(0) => {
+                    let pipe = stdin.as_mut().unwrap();
+                    while written < DATA.len() {
+                        match pipe.write(&DATA[written..]) {
+                            Ok(n) => written += n,
+                            Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                            Err(e) => panic!("write failed: {}", e),
+                        }
+                    }
+                    if written == DATA.len() {
+                        poll.deregister(pipe).unwrap();
+                        stdin = None;
+                    }
+                }
+                Token(1) => loop {
+                    match stdout.read(&mut buf) {
+                        Ok(0) => {
+                            eof = true;
+                            break;
+                        }
+                        Ok(n) => output.extend_from_slice(&buf[..n]),
+                        Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                        Err(e) => panic!("read failed: {}", e),
+                    }
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    assert!(child.wait().unwrap().success());
+    assert_eq!(output.len(), DATA.len());
+    assert!(output == DATA);
+}
@@ -0,0 +1,125 @@
+use mio_compat::process::Child;
+use mio_compat::unix::{SignalInfo, Signals};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::ErrorKind;
+use std::mem;
+use std::process::Command;
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+
+fn handler(signal: libc::c_int) -> libc::sighandler_t {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        assert_eq!(libc::sigaction(signal, ptr::null(), &mut action), 0);
+        action.sa_sigaction
+    }
+}
+
+fn recv_all(signals: &Signals) -> Vec<SignalInfo> {
+    let mut received = Vec::new();
+    loop {
+        match signals.recv() {
+            Ok(info) => received.push(info),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => return received,
+            Err(e) => panic!("recv failed: {}", e),
+        }
+    }
+}
+
+#[test]
+pub fn unix_signals() {
+    let signals = Signals::new(&[libc::SIGUSR1, libc::SIGUSR2]).unwrap();
+    let poll = Poll::new().unwrap();
+    poll.register(&signals, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+    assert_eq!(ErrorKind::WouldBlock, signals.recv().unwrap_err().kind());
+
+    // `raise` signals the calling thread, leaving the other tests alone.
+    unsafe {
+        libc::raise(libc::SIGUSR1);
+        libc::raise(libc::SIGUSR2);
+    }
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), Token(0));
+
+    let mut received: Vec<_> = recv_all(&signals)
+        .into_iter()
+        .map(|info| {
+            assert_eq!(info.pid(), unsafe { libc::getpid() });
+            assert_eq!(info.uid(), unsafe { libc::getuid() });
+            info.signal()
+        })
+        .collect();
+    received.sort();
+    assert_eq!(received, [libc::SIGUSR1, libc::SIGUSR2]);
+
+    drop(signals);
+    assert_eq!(handler(libc::SIGUSR1), libc::SIG_DFL);
+    assert_eq!(handler(libc::SIGUSR2), libc::SIG_DFL);
+}
+
+#[test]
+pub fn unix_signals_other_thread() {
+    let signals = Signals::new(&[libc::SIGHUP]).unwrap();
+    let poll = Poll::new().unwrap();
+    poll.register(&signals, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    // Sent to the process, so any thread may take it.
+    thread::spawn(|| unsafe {
+        assert_eq!(libc::kill(libc::getpid(), libc::SIGHUP), 0);
+    })
+    .join()
+    .unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), Token(0));
+
+    let received = recv_all(&signals);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].signal(), libc::SIGHUP);
+    assert_eq!(received[0].code(), libc::SI_USER);
+}
+
+#[test]
+pub fn unix_signals_child_exit() {
+    let signals = Signals::new(&[libc::SIGCHLD]).unwrap();
+    let poll = Poll::new().unwrap();
+    poll.register(&signals, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut child = Child::spawn(Command::new("/bin/sh").args(["-c", "exit 7"])).unwrap();
+    let pid = child.id() as libc::pid_t;
+    let mut exited = false;
+    while !exited {
+        poll.poll(&mut events, Some(Duration::from_secs(5)))
+            .unwrap();
+        assert!(!events.is_empty(), "no SIGCHLD");
+        // Other tests spawn children too.
+        exited = recv_all(&signals)
+            .iter()
+            .any(|info| info.pid() == pid && info.status() == 7);
+    }
+
+    // The exit is still reported through `ChildExit` once `Signals` is gone.
+    drop(signals);
+    poll.register(&child.exit, Token(1), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), Token(1));
+    assert_eq!(child.exit.try_wait().unwrap().unwrap().code(), Some(7));
+}
+
+#[test]
+pub fn unix_signals_invalid() {
+    for &signal in &[0, -1, 1000] {
+        let err = Signals::new(&[signal]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,224 @@
+use mio_compat::unix::{UnixListener, UnixStream};
+use mio_compat::{Events, Poll, PollOpt, Ready, Token};
+use std::io::{ErrorKind, Read, Write};
+use std::net::Shutdown;
+use tempdir::TempDir;
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+const CONN: Token = Token(2);
+
+const MSG: &[u8] = b"hello unix socket";
+
+#[test]
+pub fn unix_stream_echo() {
+    let dir = TempDir::new("mio-compat").unwrap();
+    let path = dir.path().join("sock");
+
+    let listener = UnixListener::bind(&path).unwrap();
+    assert_eq!(listener.local_addr().unwrap().as_pathname(), Some(&*path));
+    assert_eq!(ErrorKind::WouldBlock, listener.accept().unwrap_err().kind());
+
+    let poll = Poll::new().unwrap();
+    poll.register(&listener, SERVER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut client = UnixStream::connect(&path).unwrap();
+    poll.register(
+        &client,
+        CLIENT,
+        Ready::readable() | Ready::writable(),
+        PollOpt::edge(),
+    )
+    .unwrap();
+
+    let mut events = Events::with_capacity(16);
+    let mut conn = None;
+    let mut written = false;
+    let mut echoed = Vec::new();
+
+    while echoed.len() < MSG.len() {
+        poll.poll(&mut events, None).unwrap();
+
+        for event in &events {
+            match event.token() {
+                SERVER => {
+                    let (stream, _) = listener.accept().unwrap();
+                    poll.register(&stream, CONN, Ready::readable(), PollOpt::edge())
+                        .unwrap();
+                    conn = Some(stream);
+                }
+                CLIENT if event.readiness().is_writable() && !written => {
+                    client.write_all(MSG).unwrap();
+                    written = true;
+                }
+                CLIENT => {}
+                CONN => {
+                    // Echo through a borrowed stream.
+                    let mut stream = conn.as_ref().unwrap();
+                    let mut buf = [0; 64];
+                    loop {
+                        match stream.read(&mut buf) {
+                            Ok(0) => break,
+                            Ok(n) => stream.write_all(&buf[..n]).unwrap(),
+                            Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                            Err(e) => panic!("read failed: {}", e),
+                        }
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        let mut buf = [0; 64];
+        match client.read(&mut buf) {
+            Ok(n) => echoed.extend_from_slice(&buf[..n]),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+    assert_eq!(echoed, MSG);
+    assert!(client.take_error().unwrap().is_none());
+}
+
+#[test]
+pub fn unix_stream_pair_shutdown() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    assert!(a.peer_addr().unwrap().is_unnamed());
+
+    let poll = Poll::new().unwrap();
+    poll.register(&b, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut buf = [0; 16];
+    assert_eq!(ErrorKind::WouldBlock, b.read(&mut buf).unwrap_err().kind());
+
+    a.write_all(b"ping").unwrap();
+    a.shutdown(Shutdown::Write).unwrap();
+
+    let mut received = Vec::new();
+    loop {
+        match b.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => received.extend_from_slice(&buf[..n]),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+    assert_eq!(received, b"ping");
+}
+
+#[test]
+pub fn unix_stream_pass_fds() {
+    use std::fs::File;
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    let (a, b) = UnixStream::pair().unwrap();
+    let (reader, writer) = {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) }
+    };
+
+    let poll = Poll::new().unwrap();
+    poll.register(&b, Token(0), Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    assert_eq!(
+        a.send_with_fds(b"x", &[reader.as_raw_fd(), writer.as_raw_fd()])
+            .unwrap(),
+        1
+    );
+    drop(reader);
+
+    let mut buf = [0; 16];
+    let mut fds = Vec::new();
+    let meta = loop {
+        match b.recv_with_fds(&mut buf, &mut fds) {
+            Ok(meta) => break meta,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("recv_with_fds failed: {}", e),
+        }
+    };
+    assert_eq!(meta.len(), 1);
+    assert_eq!(&buf[..1], b"x");
+    assert!(!meta.fds_truncated());
+    assert_eq!(fds.len(), 2);
+    for fd in &fds {
+        let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+        assert!(flags & libc::FD_CLOEXEC != 0);
+    }
+
+    // The received descriptors refer to the same pipe.
+    (&writer).write_all(b"through the pipe").unwrap();
+    drop(writer);
+    let mut received_reader = unsafe { File::from_raw_fd(fds.remove(0).into_raw_fd()) };
+    drop(fds);
+    let mut contents = String::new();
+    received_reader.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "through the pipe");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn unix_stream_abstract_addr() {
+    use mio_compat::unix::{SocketAddr, UnixDatagram};
+
+    let name = format!("mio-compat-test-{}", std::process::id());
+    let addr = SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+    assert_eq!(addr.as_abstract_name(), Some(name.as_bytes()));
+    assert!(addr.as_pathname().is_none());
+    assert!(!addr.is_unnamed());
+
+    // An empty pathname is only its terminating nul, not an abstract name.
+    let empty = SocketAddr::from_pathname("").unwrap();
+    assert_eq!(empty.as_pathname(), Some(std::path::Path::new("")));
+    assert!(empty.as_abstract_name().is_none());
+
+    let listener = UnixListener::bind_addr(&addr).unwrap();
+    assert_eq!(
+        listener.local_addr().unwrap().as_abstract_name(),
+        Some(name.as_bytes())
+    );
+    let poll = Poll::new().unwrap();
+    poll.register(&listener, SERVER, Ready::readable(), PollOpt::edge())
+        .unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let client = UnixStream::connect_addr(&addr).unwrap();
+    assert_eq!(
+        client.peer_addr().unwrap().as_abstract_name(),
+        Some(name.as_bytes())
+    );
+    assert!(client.local_addr().unwrap().is_unnamed());
+    let (_conn, peer) = loop {
+        match listener.accept() {
+            Ok(res) => break res,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                poll.poll(&mut events, None).unwrap();
+            }
+            Err(e) => panic!("accept failed: {}", e),
+        }
+    };
+    assert!(peer.is_unnamed());
+
+    // Datagram sockets share the namespace but not the addresses.
+    let dgram_addr = SocketAddr::from_abstract_name(format!("{}-dgram", name)).unwrap();
+    let server = UnixDatagram::bind_addr(&dgram_addr).unwrap();
+    let sender_addr = SocketAddr::from_abstract_name(format!("{}-sender", name)).unwrap();
+    let sender = UnixDatagram::bind_addr(&sender_addr).unwrap();
+    sender.send_to_addr(b"hello", &dgram_addr).unwrap();
+    let mut buf = [0; 16];
+    let (n, from) = server.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"hello");
+    assert_eq!(from.as_abstract_name(), sender_addr.as_abstract_name());
+    assert_eq!(
+        format!("{:?}", from),
+        format!("\"{}-sender\" (abstract)", name)
+    );
+}